@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::errors::error::prelude::*;
+
+/// A single store-and-forward message, queued for a recipient who was
+/// offline when it arrived.
+#[derive(Debug, Clone)]
+pub struct StoredMessage {
+    pub message_id: String,
+    pub recipient_key: String,
+    /// Opaque, already-encrypted payload. The store never looks inside it.
+    pub payload: Vec<u8>,
+    pub received_at: u64,
+}
+
+/// Aggregate counters for one recipient's queue, mirroring the fields the
+/// Pickup protocol's `Status` message reports.
+#[derive(Debug, Clone, Default)]
+pub struct QueueStatus {
+    pub message_count: u32,
+    pub oldest_received_time: Option<u64>,
+    pub newest_received_time: Option<u64>,
+    pub total_bytes: u64,
+}
+
+/// Shared queue abstraction backing both the `forward` subsystem (producer)
+/// and the Pickup protocol (consumer). Implementations must guarantee
+/// at-least-once delivery: a message is only removed once the recipient has
+/// explicitly acknowledged it via `ack`, never merely because it was handed
+/// out by `take`. `Send + Sync` and async so a mediator binary can run many
+/// instances against one shared backend (e.g. a database).
+#[async_trait]
+pub trait MessageStore: Send + Sync {
+    async fn enqueue(&self, message: StoredMessage) -> VcxResult<()>;
+
+    async fn status(&self, recipient_key: &str) -> VcxResult<QueueStatus>;
+
+    /// Hand out up to `limit` queued messages for `recipient_key`, oldest
+    /// first. Messages remain in the store until `ack` is called for them.
+    async fn take(&self, recipient_key: &str, limit: u32) -> VcxResult<Vec<StoredMessage>>;
+
+    /// Remove messages the recipient confirmed it has durably stored.
+    async fn ack(&self, recipient_key: &str, message_ids: &[String]) -> VcxResult<()>;
+}
+
+#[derive(Debug, Default)]
+struct RecipientQueue {
+    messages: Vec<StoredMessage>,
+}
+
+/// Default, single-process `MessageStore`. Fine for tests and small
+/// deployments; a horizontally-scaled mediator should back `MessageStore`
+/// with a shared database instead (see `DbMessageStore`).
+#[derive(Debug, Default)]
+pub struct InMemoryMessageStore {
+    queues: RwLock<HashMap<String, RecipientQueue>>,
+}
+
+impl InMemoryMessageStore {
+    pub fn new() -> Self {
+        InMemoryMessageStore::default()
+    }
+}
+
+#[async_trait]
+impl MessageStore for InMemoryMessageStore {
+    async fn enqueue(&self, message: StoredMessage) -> VcxResult<()> {
+        let mut queues = self.queues.write().await;
+        queues
+            .entry(message.recipient_key.clone())
+            .or_default()
+            .messages
+            .push(message);
+        Ok(())
+    }
+
+    async fn status(&self, recipient_key: &str) -> VcxResult<QueueStatus> {
+        let queues = self.queues.read().await;
+        let messages = queues
+            .get(recipient_key)
+            .map(|q| q.messages.as_slice())
+            .unwrap_or(&[]);
+
+        let total_bytes = messages.iter().map(|m| m.payload.len() as u64).sum();
+        let oldest_received_time = messages.iter().map(|m| m.received_at).min();
+        let newest_received_time = messages.iter().map(|m| m.received_at).max();
+
+        Ok(QueueStatus {
+            message_count: messages.len() as u32,
+            oldest_received_time,
+            newest_received_time,
+            total_bytes,
+        })
+    }
+
+    async fn take(&self, recipient_key: &str, limit: u32) -> VcxResult<Vec<StoredMessage>> {
+        let queues = self.queues.read().await;
+        Ok(queues
+            .get(recipient_key)
+            .map(|q| q.messages.iter().take(limit as usize).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn ack(&self, recipient_key: &str, message_ids: &[String]) -> VcxResult<()> {
+        let mut queues = self.queues.write().await;
+        if let Some(queue) = queues.get_mut(recipient_key) {
+            queue
+                .messages
+                .retain(|m| !message_ids.contains(&m.message_id));
+        }
+        Ok(())
+    }
+}
+
+/// The actual enqueue/status/take/ack operations a database-backed
+/// `MessageStore` needs, left for the integrator to implement against
+/// their chosen storage crate (e.g. a `sqlx::PgPool` newtype) rather than
+/// hardcoded here. `DbMessageStore` only adapts this to `MessageStore`.
+#[async_trait]
+pub trait MessageStoreBackend: Send + Sync {
+    async fn enqueue(&self, message: StoredMessage) -> VcxResult<()>;
+
+    async fn status(&self, recipient_key: &str) -> VcxResult<QueueStatus>;
+
+    async fn take(&self, recipient_key: &str, limit: u32) -> VcxResult<Vec<StoredMessage>>;
+
+    async fn ack(&self, recipient_key: &str, message_ids: &[String]) -> VcxResult<()>;
+}
+
+/// Database-backed `MessageStore` for mediators that need to scale
+/// horizontally across multiple instances against one shared backend. Wraps
+/// a connection pool and delegates every operation to it via
+/// `MessageStoreBackend`, so the schema/queries live with the chosen
+/// storage crate rather than hardcoded here.
+pub struct DbMessageStore<Pool> {
+    pool: Arc<Pool>,
+}
+
+impl<Pool> DbMessageStore<Pool> {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        DbMessageStore { pool }
+    }
+}
+
+#[async_trait]
+impl<Pool: MessageStoreBackend> MessageStore for DbMessageStore<Pool> {
+    async fn enqueue(&self, message: StoredMessage) -> VcxResult<()> {
+        self.pool.enqueue(message).await
+    }
+
+    async fn status(&self, recipient_key: &str) -> VcxResult<QueueStatus> {
+        self.pool.status(recipient_key).await
+    }
+
+    async fn take(&self, recipient_key: &str, limit: u32) -> VcxResult<Vec<StoredMessage>> {
+        self.pool.take(recipient_key, limit).await
+    }
+
+    async fn ack(&self, recipient_key: &str, message_ids: &[String]) -> VcxResult<()> {
+        self.pool.ack(recipient_key, message_ids).await
+    }
+}
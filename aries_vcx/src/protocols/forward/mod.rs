@@ -0,0 +1,119 @@
+pub mod message_store;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use messages::protocols::pickup::{Delivery, MessagesReceived, Status};
+use messages::protocols::routing::forward::Forward;
+
+use crate::errors::error::prelude::*;
+use crate::protocols::forward::message_store::{MessageStore, StoredMessage};
+
+/// Mediator-side store-and-forward handling: decode an Aries RFC 0094
+/// `Forward`, confirm the destination key is one we route for, and enqueue
+/// the still-encrypted payload. The same `MessageStore` backs the Pickup
+/// protocol (RFC 0685) server side below, so a message enqueued here shows
+/// up in `Status`/`Delivery` for that recipient.
+pub struct ForwardAgent {
+    store: Arc<dyn MessageStore>,
+}
+
+impl ForwardAgent {
+    pub fn new(store: Arc<dyn MessageStore>) -> Self {
+        ForwardAgent { store }
+    }
+
+    /// `known_keylist` is the set of recipient keys this mediator currently
+    /// routes for, as maintained by the Coordinate Mediation subsystem.
+    /// Forwarding to a key outside that set is rejected rather than silently
+    /// queued, since we have no agent to ever deliver it to.
+    pub async fn handle_forward(
+        &self,
+        forward: Forward,
+        known_keylist: &HashSet<String>,
+        received_at: u64,
+    ) -> VcxResult<()> {
+        if !known_keylist.contains(&forward.to) {
+            return Err(AriesVcxError::from_msg(
+                AriesVcxErrorKind::InvalidState,
+                format!("No recipient registered for routing key {}", forward.to),
+            ));
+        }
+
+        let payload = serde_json::to_vec(&forward.msg).map_err(|err| {
+            AriesVcxError::from_msg(
+                AriesVcxErrorKind::InvalidJson,
+                format!("Forward message payload is not valid JSON: {}", err),
+            )
+        })?;
+
+        let message_id = uuid::Uuid::new_v4().to_string();
+
+        self.store
+            .enqueue(StoredMessage {
+                message_id,
+                recipient_key: forward.to,
+                payload,
+                received_at,
+            })
+            .await
+    }
+
+    /// Build a Pickup `Status` for `recipient_key` from the queue's current
+    /// counters.
+    pub async fn status_for(&self, recipient_key: &str) -> VcxResult<Status> {
+        let status = self.store.status(recipient_key).await?;
+        Ok(Status {
+            message_count: status.message_count,
+            recipient_key: Some(recipient_key.to_string()),
+            longest_waited_seconds: status
+                .oldest_received_time
+                .map(|oldest| received_at_to_wait_seconds(oldest)),
+            newest_received_time: status.newest_received_time.map(|t| t.to_string()),
+            oldest_received_time: status.oldest_received_time.map(|t| t.to_string()),
+            total_bytes: Some(status.total_bytes),
+            live_delivery: false,
+        })
+    }
+
+    /// Build a Pickup `Delivery` handing out up to `limit` queued messages.
+    /// Messages stay in the store until the recipient sends back
+    /// `MessagesReceived` (see `acknowledge`), so a mediator that crashes
+    /// mid-delivery redelivers rather than losing messages.
+    pub async fn delivery_for(&self, recipient_key: &str, limit: u32) -> VcxResult<Delivery> {
+        let messages = self.store.take(recipient_key, limit).await?;
+
+        let attach = messages
+            .into_iter()
+            .map(|message| {
+                messages::concepts::attachment::Attachment::new_base64(
+                    message.message_id,
+                    base64::encode(message.payload),
+                )
+            })
+            .collect();
+
+        Ok(Delivery {
+            recipient_key: Some(recipient_key.to_string()),
+            attach,
+        })
+    }
+
+    pub async fn acknowledge(
+        &self,
+        recipient_key: &str,
+        ack: MessagesReceived,
+    ) -> VcxResult<()> {
+        self.store.ack(recipient_key, &ack.message_id_list).await
+    }
+}
+
+/// Seconds elapsed between `received_at` (a Unix timestamp) and now.
+fn received_at_to_wait_seconds(received_at: u64) -> u64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(received_at);
+
+    now.saturating_sub(received_at)
+}
@@ -1,6 +1,8 @@
 use crate::protocols::mediated_connection::invitee::states::requested::RequestedState;
+use crate::protocols::mediated_connection::protocol_version::negotiate_version;
 use messages::diddoc::aries::diddoc::AriesDidDoc;
 use messages::protocols::connection::invite::Invitation;
+use messages::protocols::connection::problem_report::ProblemReport;
 use messages::protocols::connection::request::Request;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -9,9 +11,18 @@ pub struct InvitedState {
     pub did_doc: AriesDidDoc,
 }
 
-impl From<(InvitedState, Request, AriesDidDoc)> for RequestedState {
-    fn from((_state, request, did_doc): (InvitedState, Request, AriesDidDoc)) -> RequestedState {
+impl TryFrom<(InvitedState, Request, AriesDidDoc)> for RequestedState {
+    type Error = ProblemReport;
+
+    fn try_from(
+        (_state, request, did_doc): (InvitedState, Request, AriesDidDoc),
+    ) -> Result<RequestedState, ProblemReport> {
         trace!("ConnectionInvitee: transit state from InvitedState to RequestedState");
-        RequestedState { request, did_doc }
+        let negotiated_version = negotiate_version(&request)?;
+        Ok(RequestedState {
+            request,
+            did_doc,
+            negotiated_version,
+        })
     }
 }
@@ -0,0 +1,19 @@
+use messages::diddoc::aries::diddoc::AriesDidDoc;
+use messages::protocols::connection::request::Request;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequestedState {
+    pub request: Request,
+    pub did_doc: AriesDidDoc,
+    /// Highest minor version of the connection/DIDExchange protocol both
+    /// parties support, negotiated from the counterparty's `Request`. Higher
+    /// layers (issuer_credential, proof, disclosed_proof) can branch on this
+    /// to support the legacy connection protocol and newer DIDExchange side
+    /// by side instead of hard-coding one version per call site.
+    ///
+    /// `#[serde(default)]` so a connection persisted before this field
+    /// existed still deserializes, falling back to `0` (the same value a
+    /// connections 1.0 negotiation produces) rather than failing.
+    #[serde(default)]
+    pub negotiated_version: u32,
+}
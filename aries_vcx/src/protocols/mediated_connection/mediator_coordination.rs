@@ -0,0 +1,107 @@
+use messages::diddoc::aries::diddoc::AriesDidDoc;
+use messages::protocols::coordinate_mediation::{
+    Keylist, KeylistQuery, KeylistUpdate, KeylistUpdateAction, KeylistUpdateEntry,
+    KeylistUpdateResponse, MediateDeny, MediateGrant, MediateRequest,
+};
+
+use crate::errors::error::prelude::*;
+use crate::protocols::mediated_connection::mediated_connection::MediatedConnection;
+
+/// What an agent learned from a mediator's `MediateGrant`: where to send
+/// messages for it, and which routing keys downstream connections must wrap
+/// their envelopes with. `apply_routing` embeds `routing_keys` in a DID doc's
+/// service blocks so counterparties know to route through this mediator; a
+/// DID doc is not changed unless `apply_routing` is called on it.
+#[derive(Debug, Clone, Default)]
+pub struct MediatorCoordination {
+    pub endpoint: Option<String>,
+    pub routing_keys: Vec<String>,
+}
+
+impl MediatorCoordination {
+    pub fn new() -> Self {
+        MediatorCoordination::default()
+    }
+
+    pub fn is_mediated(&self) -> bool {
+        self.endpoint.is_some()
+    }
+
+    /// Embed the mediator's routing keys into a freshly built DID doc so that
+    /// connections created after mediation was granted route through it.
+    pub fn apply_routing(&self, did_doc: &mut AriesDidDoc) {
+        if self.routing_keys.is_empty() {
+            return;
+        }
+        for service in did_doc.service.iter_mut() {
+            for key in &self.routing_keys {
+                if !service.routing_keys.contains(key) {
+                    service.routing_keys.push(key.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Send a `MediateRequest` over an already-completed connection and, on
+/// `MediateGrant`, record the mediator's endpoint and routing keys so
+/// subsequently created connections can embed them. Aries RFC 0211.
+pub async fn request_mediation(
+    connection: &MediatedConnection,
+) -> VcxResult<MediatorCoordination> {
+    let request = MediateRequest::new();
+    let response = connection.send_message_and_wait_for_reply(&request.into()).await?;
+
+    if let Ok(deny) = MediateDeny::try_from(response.clone()) {
+        return Err(AriesVcxError::from_msg(
+            AriesVcxErrorKind::MediationRequestDenied,
+            format!(
+                "Mediator denied mediation request: {}",
+                deny.reason.unwrap_or_else(|| "no reason given".to_string())
+            ),
+        ));
+    }
+
+    let grant: MediateGrant = response.try_into()?;
+    Ok(MediatorCoordination {
+        endpoint: Some(grant.endpoint),
+        routing_keys: grant.routing_keys,
+    })
+}
+
+async fn keylist_update(
+    connection: &MediatedConnection,
+    recipient_key: String,
+    action: KeylistUpdateAction,
+) -> VcxResult<KeylistUpdateResponse> {
+    let update = KeylistUpdate::new(vec![KeylistUpdateEntry {
+        recipient_key,
+        action,
+    }]);
+    connection
+        .send_message_and_wait_for_reply(&update.into())
+        .await?
+        .try_into()
+}
+
+pub async fn keylist_update_add(
+    connection: &MediatedConnection,
+    recipient_key: String,
+) -> VcxResult<KeylistUpdateResponse> {
+    keylist_update(connection, recipient_key, KeylistUpdateAction::Add).await
+}
+
+pub async fn keylist_update_remove(
+    connection: &MediatedConnection,
+    recipient_key: String,
+) -> VcxResult<KeylistUpdateResponse> {
+    keylist_update(connection, recipient_key, KeylistUpdateAction::Remove).await
+}
+
+pub async fn keylist_query(connection: &MediatedConnection) -> VcxResult<Keylist> {
+    let query = KeylistQuery::default();
+    connection
+        .send_message_and_wait_for_reply(&query.into())
+        .await?
+        .try_into()
+}
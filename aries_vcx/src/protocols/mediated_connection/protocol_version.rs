@@ -0,0 +1,175 @@
+use messages::protocols::connection::problem_report::{ProblemReport, ProblemReportCodes};
+use messages::protocols::connection::request::Request;
+
+/// Minor versions of the `connections` / `didexchange` protocol family this
+/// agent supports for the `1.x` major line. DIDExchange (a separate major
+/// version) is negotiated the same way once it lands; for now we only speak
+/// connection protocol 1.0-1.2.
+const OUR_MIN_MINOR_VERSION: u32 = 0;
+const OUR_MAX_MINOR_VERSION: u32 = 2;
+
+/// The version a `Request` advertises and, derived from the same `@type`
+/// field, the counterparty's minimum required version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestedProtocolVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub min_supported_minor: Option<u32>,
+}
+
+/// Parse the protocol major/minor out of a message's `@type`
+/// (`.../connections/<major>.<minor>/request`). We go through
+/// `serde_json::Value` rather than assuming particular struct fields, since
+/// `Request` is shared with the legacy connection protocol and DIDExchange,
+/// which don't always carry the same decorators.
+pub fn parse_requested_version(request: &Request) -> RequestedProtocolVersion {
+    let value = serde_json::to_value(request).unwrap_or(serde_json::Value::Null);
+    parse_requested_version_from_value(&value)
+}
+
+/// The actual parsing logic behind `parse_requested_version`, split out so
+/// it can be driven directly in tests without a real `Request` (a `~thread`
+/// `protocols`/`min_version` discovery decorator doesn't exist on any Aries
+/// connection message, so there's no such field to read it from). `@type`'s
+/// own minor version is the only genuine version information a `Request`
+/// carries on the wire, and it doubles as the counterparty's minimum
+/// required version: this is the version they actually used to build the
+/// message, so anything short of it can't be assumed compatible.
+fn parse_requested_version_from_value(value: &serde_json::Value) -> RequestedProtocolVersion {
+    let (major, minor) = value["@type"]
+        .as_str()
+        .and_then(parse_type_version)
+        .unwrap_or((1, 0));
+
+    RequestedProtocolVersion {
+        major,
+        minor,
+        min_supported_minor: Some(minor),
+    }
+}
+
+fn parse_type_version(type_: &str) -> Option<(u32, u32)> {
+    let version_segment = type_.split('/').rev().nth(1)?;
+    let mut parts = version_segment.splitn(2, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Compute the highest minor version both parties support, or a problem
+/// report if the counterparty's minimum required version is newer than
+/// anything we speak.
+pub fn negotiate_version(request: &Request) -> Result<u32, ProblemReport> {
+    negotiate_requested_version(parse_requested_version(request))
+}
+
+/// The actual negotiation logic behind `negotiate_version`, split out so it
+/// can be driven directly in tests against a `RequestedProtocolVersion`
+/// without needing a real `Request`.
+fn negotiate_requested_version(requested: RequestedProtocolVersion) -> Result<u32, ProblemReport> {
+    if requested.major != 1 {
+        return Err(ProblemReport::create()
+            .set_problem_code(ProblemReportCodes::RequestProcessingError)
+            .set_explain(format!(
+                "Unsupported connection protocol major version: {}",
+                requested.major
+            )));
+    }
+
+    if let Some(min_required) = requested.min_supported_minor {
+        if min_required > OUR_MAX_MINOR_VERSION {
+            return Err(ProblemReport::create()
+                .set_problem_code(ProblemReportCodes::RequestProcessingError)
+                .set_explain(format!(
+                    "Counterparty requires at least connections 1.{}, we only support up to 1.{}",
+                    min_required, OUR_MAX_MINOR_VERSION
+                )));
+        }
+    }
+
+    let negotiated = requested.minor.min(OUR_MAX_MINOR_VERSION).max(OUR_MIN_MINOR_VERSION);
+    Ok(negotiated)
+}
+
+#[cfg(test)]
+#[cfg(feature = "general_test")]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn parses_major_minor_from_type() {
+        assert_eq!(
+            parse_type_version("https://didcomm.org/connections/1.0/request"),
+            Some((1, 0))
+        );
+        assert_eq!(
+            parse_type_version("https://didcomm.org/connections/1.2/request"),
+            Some((1, 2))
+        );
+    }
+
+    #[test]
+    fn parse_requested_version_from_value_treats_the_type_minor_as_the_minimum_required_version() {
+        let value = serde_json::json!({"@type": "https://didcomm.org/connections/1.2/request"});
+
+        assert_eq!(
+            parse_requested_version_from_value(&value),
+            RequestedProtocolVersion {
+                major: 1,
+                minor: 2,
+                min_supported_minor: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_requested_version_from_value_falls_back_to_1_0_when_type_is_missing() {
+        let value = serde_json::json!({});
+
+        assert_eq!(
+            parse_requested_version_from_value(&value),
+            RequestedProtocolVersion {
+                major: 1,
+                minor: 0,
+                min_supported_minor: Some(0),
+            }
+        );
+    }
+
+    fn requested(minor: u32) -> RequestedProtocolVersion {
+        RequestedProtocolVersion {
+            major: 1,
+            minor,
+            min_supported_minor: Some(minor),
+        }
+    }
+
+    #[test]
+    fn negotiates_down_to_the_version_a_1_0_request_advertises() {
+        assert_eq!(negotiate_requested_version(requested(0)).unwrap(), 0);
+    }
+
+    #[test]
+    fn negotiates_the_full_minor_version_a_1_2_request_advertises() {
+        assert_eq!(negotiate_requested_version(requested(2)).unwrap(), 2);
+    }
+
+    #[test]
+    fn returns_a_problem_report_when_the_counterpartys_minimum_exceeds_ours() {
+        // Connections 1.3 is newer than anything we speak
+        // (`OUR_MAX_MINOR_VERSION` is 2): unlike the old `~thread`-decorator
+        // based check (which no real message ever populated), this is
+        // driven by `@type`, so it actually fires for real traffic.
+        assert!(negotiate_requested_version(requested(3)).is_err());
+    }
+
+    #[test]
+    fn rejects_an_unsupported_major_version() {
+        let requested = RequestedProtocolVersion {
+            major: 2,
+            minor: 0,
+            min_supported_minor: Some(0),
+        };
+        assert!(negotiate_requested_version(requested).is_err());
+    }
+}
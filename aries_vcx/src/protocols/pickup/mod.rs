@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use messages::protocols::pickup::{
+    Delivery, DeliveryRequest, MessagesReceived, Status, StatusRequest,
+};
+
+use crate::errors::error::prelude::*;
+use crate::protocols::mediated_connection::mediated_connection::MediatedConnection;
+
+/// A decrypted message retrieved from a mediator's pickup queue, alongside the
+/// id the mediator expects back in `MessagesReceived` once it has been stored.
+#[derive(Debug, Clone)]
+pub struct PickedUpMessage {
+    pub id: String,
+    pub decrypted_msg: String,
+}
+
+/// Outcome of a single `pickup_messages` poll: the messages retrieved this
+/// round plus the mediator's status counters, so callers can implement backoff
+/// (e.g. poll again immediately if `message_count` is still above what was
+/// delivered, or back off when it is zero).
+#[derive(Debug, Clone)]
+pub struct PickupResult {
+    pub messages: Vec<PickedUpMessage>,
+    pub status: Status,
+}
+
+/// Poll a mediator for messages it is holding for `connection`, per Aries RFC
+/// 0685 (Pickup Protocol 2.0): StatusRequest -> Status, and if messages are
+/// pending, DeliveryRequest -> Delivery, acknowledged with MessagesReceived.
+pub async fn pickup_messages(
+    connection: &MediatedConnection,
+    limit: u32,
+    recipient_key: Option<String>,
+) -> VcxResult<PickupResult> {
+    let status_request = StatusRequest::new(recipient_key.clone());
+    let status: Status = connection
+        .send_message_and_wait_for_reply(&status_request.into())
+        .await?
+        .try_into()?;
+
+    if !status.has_pending_messages() {
+        return Ok(PickupResult {
+            messages: Vec::new(),
+            status,
+        });
+    }
+
+    let delivery_request = DeliveryRequest::new(limit, recipient_key);
+    let delivery: Delivery = connection
+        .send_message_and_wait_for_reply(&delivery_request.into())
+        .await?
+        .try_into()?;
+
+    let messages = decrypt_delivery(connection, delivery).await?;
+
+    // Deduplicate by message id within this one Delivery batch: `seen` is
+    // local to this call, so it only protects against a single Delivery
+    // response listing the same attachment twice, not against the mediator
+    // re-sending a message across separate `pickup_messages` polls (that
+    // would need dedup state persisted on `connection` itself, which this
+    // function doesn't have access to).
+    let mut seen = HashSet::new();
+    let mut deduped = Vec::with_capacity(messages.len());
+    for message in messages {
+        if seen.insert(message.id.clone()) {
+            deduped.push(message);
+        }
+    }
+
+    if !deduped.is_empty() {
+        let ack = MessagesReceived::new(deduped.iter().map(|m| m.id.clone()).collect());
+        connection.send_message(&ack.into()).await?;
+    }
+
+    Ok(PickupResult {
+        messages: deduped,
+        status,
+    })
+}
+
+async fn decrypt_delivery(
+    connection: &MediatedConnection,
+    delivery: Delivery,
+) -> VcxResult<Vec<PickedUpMessage>> {
+    let mut messages = Vec::with_capacity(delivery.attach.len());
+
+    for attachment in delivery.attach {
+        let id = attachment.id().ok_or_else(|| {
+            AriesVcxError::from_msg(
+                AriesVcxErrorKind::InvalidJson,
+                "Pickup delivery attachment is missing an `@id`",
+            )
+        })?;
+        let decrypted_msg = connection.decrypt_attachment(&attachment).await?;
+        messages.push(PickedUpMessage { id, decrypted_msg });
+    }
+
+    Ok(messages)
+}
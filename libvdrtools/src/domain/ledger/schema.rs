@@ -1,10 +1,27 @@
 use super::{
-    super::{anoncreds::schema::SchemaId, crypto::did::ShortDidValue},
-    constants::{GET_SCHEMA, SCHEMA},
+    super::{
+        anoncreds::schema::SchemaId,
+        crypto::did::{DidValue, ShortDidValue},
+    },
+    constants::{GET_SCHEMA, GET_TXN, SCHEMA},
     response::{GetReplyResultV1, ReplyType},
 };
 
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use indy_api_types::errors::prelude::*;
+
+use crate::utils::crypto::signature_serializer::serialize_signature;
+
+fn next_req_id() -> u64 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    since_epoch.as_secs() * 1_000_000_000 + since_epoch.subsec_nanos() as u64
+}
 
 #[derive(Serialize, PartialEq, Debug)]
 pub struct SchemaOperation {
@@ -20,6 +37,123 @@ impl SchemaOperation {
             _type: SCHEMA.to_string(),
         }
     }
+
+    /// Wrap this write in a `SignedLedgerRequest` addressed to `endorser`
+    /// (if any), for an author without ledger write permission to get it
+    /// counter-signed and submitted on their behalf. The request itself
+    /// comes back unsigned; callers sign `signing_input()` with the
+    /// author's (and then the endorser's) key the same way
+    /// `LedgerService::begin_multi_sign`/`add_endorser_signature` do for
+    /// other transaction types, and attach the result with
+    /// `with_author_signature`/`with_endorser_signature`.
+    ///
+    /// `protocol_version` should come from the caller's
+    /// `LedgerService::protocol_version()`, so a request built while the
+    /// service is pinned to the legacy pool protocol reflects that instead
+    /// of assuming the current one.
+    pub fn into_request(
+        self,
+        author_did: DidValue,
+        endorser: Option<ShortDidValue>,
+        protocol_version: u64,
+    ) -> SignedLedgerRequest<SchemaOperation> {
+        SignedLedgerRequest::new(author_did, next_req_id(), protocol_version, self, endorser)
+    }
+}
+
+/// An author-signed (and, for an endorser-delegated write, endorser
+/// co-signed) ledger request wrapping operation `T`. Mirrors the
+/// author-owns-the-data/writer-co-signs pattern `LedgerService` already
+/// uses for raw JSON requests (`begin_multi_sign`/`add_endorser_signature`),
+/// but as a typed wrapper around a single domain operation so a write
+/// like `SchemaOperation` can build its own endorsed request without going
+/// through the ledger service's untyped `Request<Value>` path.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct SignedLedgerRequest<T> {
+    pub identifier: DidValue,
+    #[serde(rename = "reqId")]
+    pub req_id: u64,
+    pub protocol_version: u64,
+    pub operation: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endorser: Option<ShortDidValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signatures: Option<HashMap<String, String>>,
+}
+
+impl<T> SignedLedgerRequest<T> {
+    pub fn new(
+        identifier: DidValue,
+        req_id: u64,
+        protocol_version: u64,
+        operation: T,
+        endorser: Option<ShortDidValue>,
+    ) -> SignedLedgerRequest<T> {
+        SignedLedgerRequest {
+            identifier,
+            req_id,
+            protocol_version,
+            operation,
+            signature: None,
+            endorser,
+            signatures: None,
+        }
+    }
+}
+
+impl<T: serde::Serialize> SignedLedgerRequest<T> {
+    /// The canonical bytes the author (and, for an endorsed write, the
+    /// endorser) must sign over: this request serialized with any
+    /// signature already attached stripped back out first, so every
+    /// signer is guaranteed to sign the identical bytes regardless of
+    /// where in the hand-off they're called. Goes through the same
+    /// `serialize_signature` canonicalization `LedgerService` uses for
+    /// `get_txn_bytes_to_sign`/`multi_signed_bytes_to_sign`, rather than
+    /// plain `serde_json`, since field ordering isn't normalized otherwise
+    /// and a signature produced here has to validate on-ledger.
+    pub fn signing_input(&self) -> IndyResult<Vec<u8>> {
+        let mut value = serde_json::to_value(self)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize request")?;
+
+        if let Some(map) = value.as_object_mut() {
+            map.remove("signature");
+            map.remove("signatures");
+        }
+
+        Ok(serialize_signature(value)?.as_bytes().to_vec())
+    }
+
+    /// Attach the author's detached signature as the request's single
+    /// `signature` field, for the common case of an author submitting
+    /// their own write with no endorser involved.
+    pub fn with_author_signature(mut self, signature: &[u8]) -> Self {
+        self.signature = Some(bs58::encode(signature).into_string());
+        self.signatures = None;
+        self
+    }
+
+    /// Append the author's and an endorser's detached signatures (both
+    /// computed over `signing_input()`) into the request's `signatures`
+    /// map, keyed by the short form of each signer's DID, and clear the
+    /// single-signer `signature` field so the request is unambiguous
+    /// about which signing mode it uses.
+    pub fn with_endorser_signature(
+        mut self,
+        author_signature: &[u8],
+        endorser_did: &ShortDidValue,
+        endorser_signature: &[u8],
+    ) -> Self {
+        let mut signatures = HashMap::new();
+        signatures.insert(self.identifier.to_short().0, bs58::encode(author_signature).into_string());
+        signatures.insert(endorser_did.0.clone(), bs58::encode(endorser_signature).into_string());
+
+        self.endorser = Some(endorser_did.clone());
+        self.signature = None;
+        self.signatures = Some(signatures);
+        self
+    }
 }
 
 #[derive(Serialize, PartialEq, Debug, Deserialize)]
@@ -82,6 +216,49 @@ impl ReplyType for GetSchemaReplyResult {
     }
 }
 
+/// The result of a `GET_TXN` reply (its own `type` tag, distinct from
+/// `GET_SCHEMA`'s) for a txn that wrote a `SCHEMA`: the txn's own
+/// sequence number, the author DID that submitted it, and the `SCHEMA`
+/// operation data it originally wrote. Parsed through its own
+/// `Reply<GetTxnResultData>` (see `LedgerService::parse_get_txn_schema_response`)
+/// rather than folded into `GetSchemaReplyResult`, since that enum's
+/// `ReplyType::get_type()` is hardcoded to `GET_SCHEMA` and would reject
+/// a real `GET_TXN` reply before ever reaching this variant.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTxnResultData {
+    pub seq_no: u32,
+    pub txn: GetTxnTxnData,
+}
+
+impl ReplyType for GetTxnResultData {
+    fn get_type<'a>() -> &'a str {
+        GET_TXN
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetTxnTxnData {
+    pub data: SchemaOperationData,
+    pub metadata: GetTxnTxnMetadata,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetTxnTxnMetadata {
+    pub from: ShortDidValue,
+}
+
+impl GetTxnResultData {
+    /// Hydrate the author DID and `SchemaOperationData`
+    /// (`name`/`version`/`attr_names`) this txn wrote, the same data
+    /// `GET_SCHEMA` returns, so a caller that only has a seq_no can
+    /// resolve a schema without the name/version round-trip
+    /// `GetSchemaOperationData` needs.
+    pub fn into_schema_data(self) -> (ShortDidValue, SchemaOperationData) {
+        (self.txn.metadata.from, self.txn.data)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GetSchemaResultV0 {
@@ -105,3 +282,104 @@ pub struct GetSchemaResultDataV1 {
 pub struct GetSchemaResultDataValueV1 {
     pub attr_names: HashSet<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const AUTHOR_DID: &str = "V4SGRU86Z58d6TV7PBUe6f";
+    const ENDORSER_DID: &str = "2hoqvcwupRTUNkXn6ArYzs";
+    const LEGACY_PROTOCOL_VERSION: u64 = 1;
+    const CURRENT_PROTOCOL_VERSION: u64 = 2;
+
+    fn author() -> DidValue {
+        DidValue(AUTHOR_DID.to_string())
+    }
+
+    fn endorser() -> ShortDidValue {
+        ShortDidValue(ENDORSER_DID.to_string())
+    }
+
+    fn schema_operation() -> SchemaOperation {
+        SchemaOperation::new(SchemaOperationData::new(
+            "gvt".to_string(),
+            "1.0".to_string(),
+            vec!["name".to_string(), "age".to_string()].into_iter().collect(),
+        ))
+    }
+
+    #[test]
+    fn into_request_uses_the_protocol_version_passed_in_rather_than_a_hardcoded_one() {
+        let legacy = schema_operation().into_request(author(), None, LEGACY_PROTOCOL_VERSION);
+        assert_eq!(LEGACY_PROTOCOL_VERSION, legacy.protocol_version);
+
+        let current = schema_operation().into_request(author(), None, CURRENT_PROTOCOL_VERSION);
+        assert_eq!(CURRENT_PROTOCOL_VERSION, current.protocol_version);
+    }
+
+    #[test]
+    fn signing_input_strips_any_signature_already_present() {
+        let request = schema_operation()
+            .into_request(author(), None, CURRENT_PROTOCOL_VERSION)
+            .with_author_signature(b"author-signature");
+
+        let bytes = request.signing_input().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert!(value.get("signature").is_none());
+        assert!(value.get("signatures").is_none());
+        assert_eq!(value["identifier"], json!(AUTHOR_DID));
+    }
+
+    #[test]
+    fn signing_input_matches_the_canonical_signature_serialization() {
+        // `signing_input` must agree with `serialize_signature` byte for
+        // byte: a signature produced over anything else (e.g. plain
+        // `serde_json`, whose object key order isn't normalized) won't
+        // validate on-ledger.
+        let request = schema_operation().into_request(author(), None, CURRENT_PROTOCOL_VERSION);
+
+        let mut expected = serde_json::to_value(&request).unwrap();
+        expected.as_object_mut().unwrap().remove("signature");
+        expected.as_object_mut().unwrap().remove("signatures");
+
+        assert_eq!(
+            serialize_signature(expected).unwrap().as_bytes().to_vec(),
+            request.signing_input().unwrap()
+        );
+    }
+
+    #[test]
+    fn with_author_signature_sets_signature_and_clears_signatures() {
+        let request = schema_operation()
+            .into_request(author(), None, CURRENT_PROTOCOL_VERSION)
+            .with_author_signature(b"author-signature");
+
+        assert_eq!(
+            Some(bs58::encode(b"author-signature").into_string()),
+            request.signature
+        );
+        assert!(request.signatures.is_none());
+    }
+
+    #[test]
+    fn with_endorser_signature_populates_signatures_map_and_clears_signature() {
+        let request = schema_operation()
+            .into_request(author(), Some(endorser()), CURRENT_PROTOCOL_VERSION)
+            .with_endorser_signature(b"author-signature", &endorser(), b"endorser-signature");
+
+        assert!(request.signature.is_none());
+        assert_eq!(Some(endorser()), request.endorser);
+
+        let signatures = request.signatures.expect("signatures map should be set");
+        assert_eq!(
+            bs58::encode(b"author-signature").into_string(),
+            signatures[&author().to_short().0]
+        );
+        assert_eq!(
+            bs58::encode(b"endorser-signature").into_string(),
+            signatures[&endorser().0]
+        );
+    }
+}
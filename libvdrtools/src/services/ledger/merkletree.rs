@@ -0,0 +1,676 @@
+//! Verification of the `state_proof` Indy nodes attach to `GET_*` read
+//! replies: a Merkle-Patricia-Trie inclusion proof for the ledger's state
+//! trie, plus the BLS multi-signature validators used to attest the trie
+//! root at the time of the read.
+
+use std::collections::HashMap;
+
+use hex::FromHex;
+use indy_api_types::errors::prelude::*;
+use indy_utils::crypto::hash::hash as openssl_hash;
+use serde::Deserialize;
+use ursa::bls::{Generator, MultiSignature, SignedMessage, VerKey};
+
+use crate::utils::crypto::signature_serializer::serialize_signature;
+
+/// The `state_proof` object attached to a ledger read reply.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateProof {
+    pub root_hash: String,
+    pub proof_nodes: String,
+    pub multi_signature: StateProofMultiSignature,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateProofMultiSignature {
+    pub value: StateProofMultiSignatureValue,
+    pub signature: String,
+    pub participants: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateProofMultiSignatureValue {
+    pub ledger_id: u64,
+    pub state_root_hash: String,
+    pub pool_state_root_hash: String,
+    pub txn_root_hash: String,
+    pub timestamp: u64,
+}
+
+/// The BLS verification key of every node in the pool's validator set, as
+/// found in the genesis transactions, keyed by node alias, plus the BLS
+/// `Generator` those verkeys were derived against. The generator is part of
+/// the pool's genesis material (the same one every node's verkey was
+/// generated with) and must be supplied by the caller rather than invented
+/// here: a freshly-generated, unrelated `Generator` can never validate a
+/// genuine aggregate signature.
+pub struct ValidatorSet {
+    blskeys: HashMap<String, VerKey>,
+    generator: Generator,
+}
+
+impl ValidatorSet {
+    pub fn new(blskeys: HashMap<String, VerKey>, generator: Generator) -> Self {
+        ValidatorSet { blskeys, generator }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blskeys.len()
+    }
+
+    fn minimum_honest_signers(&self) -> usize {
+        // f+1 out of n = 3f+1 validators. An empty validator set has no
+        // quorum to reach at all, so treat it as requiring zero signers
+        // rather than underflowing `n - 1`.
+        let n = self.blskeys.len();
+        if n == 0 {
+            return 0;
+        }
+        (n - 1) / 3 + 1
+    }
+}
+
+/// `ValidatorSet` plus the BFT quorum (`2f+1`) and trusting-period policy a
+/// generic reply verification needs, as opposed to the `f+1` "at least one
+/// honest signer" threshold `verify_state_proof` uses for a single already-
+/// located GET-type value. Used by `verify_reply_proof`.
+pub struct PoolValidators {
+    validators: ValidatorSet,
+    trusting_period_seconds: u64,
+}
+
+impl PoolValidators {
+    pub fn new(validators: ValidatorSet, trusting_period_seconds: u64) -> Self {
+        PoolValidators {
+            validators,
+            trusting_period_seconds,
+        }
+    }
+
+    fn minimum_quorum(&self) -> usize {
+        // 2f+1 out of n = 3f+1 validators. Same empty-set guard as
+        // `ValidatorSet::minimum_honest_signers`.
+        let n = self.validators.len();
+        if n == 0 {
+            return 0;
+        }
+        let f = (n - 1) / 3;
+        2 * f + 1
+    }
+}
+
+/// Decode `proof_nodes` (base64 of RLP-encoded Merkle-Patricia-trie nodes),
+/// walk the trie from `root_hash` along the nibble path of `key`, and
+/// confirm the leaf reached equals `expected_value`.
+pub fn verify_inclusion(
+    proof_nodes_b64: &str,
+    root_hash_b58: &str,
+    key: &[u8],
+    expected_value: &[u8],
+) -> IndyResult<bool> {
+    let proof_nodes_raw = base64::decode(proof_nodes_b64).map_err(|err| {
+        IndyError::from_msg(
+            IndyErrorKind::InvalidStructure,
+            format!("state_proof.proof_nodes is not valid base64: {}", err),
+        )
+    })?;
+
+    let root_hash = bs58::decode(root_hash_b58).into_vec().map_err(|err| {
+        IndyError::from_msg(
+            IndyErrorKind::InvalidStructure,
+            format!("state_proof.root_hash is not valid base58: {}", err),
+        )
+    })?;
+
+    let nodes = decode_rlp_node_list(&proof_nodes_raw)?;
+    let nodes_by_hash: HashMap<Vec<u8>, Vec<u8>> = nodes
+        .into_iter()
+        .map(|node| (openssl_hash(&node).unwrap_or_default(), node))
+        .collect();
+
+    let mut nibbles = to_nibbles(key);
+    let mut current_hash = root_hash;
+
+    loop {
+        let node = match nodes_by_hash.get(&current_hash) {
+            Some(node) => node,
+            // A missing intermediate node means the proof doesn't actually
+            // connect the root to the claimed leaf.
+            None => return Ok(false),
+        };
+
+        match step_trie_node(node, &mut nibbles)? {
+            TrieStep::Descend(next_hash) => current_hash = next_hash,
+            TrieStep::Leaf(value) => return Ok(nibbles.is_empty() && value == expected_value),
+            TrieStep::Empty => return Ok(expected_value.is_empty()),
+        }
+    }
+}
+
+enum TrieStep {
+    Descend(Vec<u8>),
+    Leaf(Vec<u8>),
+    Empty,
+}
+
+/// A single step of the Merkle-Patricia-Trie walk: consume as many nibbles
+/// of the key as this node's partial path covers, then either descend into
+/// a child hash or, if the path is exhausted, return the stored leaf value.
+fn step_trie_node(node: &[u8], nibbles: &mut Vec<u8>) -> IndyResult<TrieStep> {
+    let items = decode_rlp_node_list(node)?;
+
+    match items.len() {
+        // [partial_path, value] leaf/extension node.
+        2 => {
+            let path = decode_hex_prefix_path(&items[0]);
+
+            // A node's declared partial path must actually be a full
+            // prefix of the remaining key nibbles; a proof node whose path
+            // diverges from the key (or is longer than what's left of the
+            // key) doesn't prove inclusion of that key at all, so treat a
+            // mismatch the same way the branch-node case below treats a
+            // missing child: not included.
+            if path.len() > nibbles.len() || path.as_slice() != &nibbles[0..path.len()] {
+                return Ok(TrieStep::Empty);
+            }
+
+            nibbles.drain(0..path.len());
+
+            if nibbles.is_empty() {
+                Ok(TrieStep::Leaf(items[1].clone()))
+            } else {
+                Ok(TrieStep::Descend(items[1].clone()))
+            }
+        }
+        // 16 children + value branch node.
+        17 => {
+            if nibbles.is_empty() {
+                return Ok(TrieStep::Leaf(items[16].clone()));
+            }
+            let index = nibbles.remove(0) as usize;
+            if items[index].is_empty() {
+                Ok(TrieStep::Empty)
+            } else {
+                Ok(TrieStep::Descend(items[index].clone()))
+            }
+        }
+        _ => Err(IndyError::from_msg(
+            IndyErrorKind::InvalidState,
+            "Malformed Merkle-Patricia-Trie node in state proof",
+        )),
+    }
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| vec![byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Decode a Merkle-Patricia-Trie node's hex-prefix (HP) encoded partial
+/// path into its raw nibbles. The leading nibble is a flag: bit 0 marks an
+/// odd-length path (no padding nibble follows), bit 1 marks a leaf node;
+/// `verify_inclusion` only needs the decoded nibbles, since leaf-vs-
+/// extension is inferred from whether any key nibbles remain after the
+/// path is consumed.
+fn decode_hex_prefix_path(raw: &[u8]) -> Vec<u8> {
+    let mut nibbles = to_nibbles(raw);
+    if nibbles.is_empty() {
+        return nibbles;
+    }
+
+    let is_odd = nibbles.remove(0) & 0x1 != 0;
+    if !is_odd {
+        // Even-length paths have a zero padding nibble after the flag.
+        nibbles.remove(0);
+    }
+
+    nibbles
+}
+
+/// Minimal RLP list decoder sufficient for the flat node lists Indy emits
+/// (no nested lists beyond one level).
+fn decode_rlp_node_list(bytes: &[u8]) -> IndyResult<Vec<Vec<u8>>> {
+    let rlp = rlp::Rlp::new(bytes);
+    rlp.as_list::<Vec<u8>>().map_err(|err| {
+        IndyError::from_msg(
+            IndyErrorKind::InvalidStructure,
+            format!("Cannot RLP-decode state proof node: {}", err),
+        )
+    })
+}
+
+/// Recompute the bytes that were signed (the serialized `multi_signature.value`)
+/// and verify the aggregate BLS signature against the participating
+/// validators' verkeys, requiring at least `f+1` signers.
+pub fn verify_multi_signature(
+    multi_signature: &StateProofMultiSignature,
+    validators: &ValidatorSet,
+) -> IndyResult<()> {
+    verify_multi_signature_with_threshold(
+        multi_signature,
+        &validators.blskeys,
+        &validators.generator,
+        validators.minimum_honest_signers(),
+    )
+}
+
+fn verify_multi_signature_with_threshold(
+    multi_signature: &StateProofMultiSignature,
+    blskeys: &HashMap<String, VerKey>,
+    generator: &Generator,
+    required_signers: usize,
+) -> IndyResult<()> {
+    if multi_signature.participants.len() < required_signers {
+        return Err(IndyError::from_msg(
+            IndyErrorKind::InvalidState,
+            format!(
+                "state_proof multi-signature has {} participants, need at least {}",
+                multi_signature.participants.len(),
+                required_signers
+            ),
+        ));
+    }
+
+    let mut verkeys = Vec::with_capacity(multi_signature.participants.len());
+    for alias in &multi_signature.participants {
+        let verkey = blskeys.get(alias).ok_or_else(|| {
+            IndyError::from_msg(
+                IndyErrorKind::InvalidState,
+                format!("Unknown validator in state proof participants: {}", alias),
+            )
+        })?;
+        verkeys.push(verkey);
+    }
+
+    // Indy-node signs the canonical signing serialization of `value`
+    // (field-sorted, the same way transaction signing input is built), not
+    // plain `serde_json` output: `HashMap`/object key order isn't
+    // normalized by `serde_json::to_vec`, so a bit-identical reply signed
+    // twice could serialize differently and fail to verify.
+    let signed_value = serialize_signature(serde_json::to_value(&multi_signature.value).to_indy(
+        IndyErrorKind::InvalidState,
+        "Cannot serialize state_proof multi_signature value",
+    )?)?
+    .as_bytes()
+    .to_vec();
+
+    let signature_bytes = Vec::from_hex(&multi_signature.signature)
+        .or_else(|_| bs58::decode(&multi_signature.signature).into_vec())
+        .map_err(|_| {
+            IndyError::from_msg(
+                IndyErrorKind::InvalidStructure,
+                "state_proof.multi_signature.signature is neither valid hex nor base58",
+            )
+        })?;
+
+    let signature = MultiSignature::from_bytes(&signature_bytes).map_err(|err| {
+        IndyError::from_msg(
+            IndyErrorKind::InvalidStructure,
+            format!("Cannot parse BLS multi-signature: {:?}", err),
+        )
+    })?;
+
+    let valid = signature
+        .verify(&signed_value, verkeys.into_iter().collect::<Vec<_>>().as_slice(), generator)
+        .unwrap_or(false);
+
+    if !valid {
+        return Err(IndyError::from_msg(
+            IndyErrorKind::InvalidState,
+            "BLS multi-signature over state_proof did not verify",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Full state-proof check: trie inclusion of `(key, expected_value)` under
+/// `state_proof.root_hash`, plus the BLS multi-signature over that root.
+pub fn verify_state_proof(
+    state_proof: &StateProof,
+    key: &[u8],
+    expected_value: &[u8],
+    validators: &ValidatorSet,
+) -> IndyResult<()> {
+    // The trie we just walked and the trie root the validators actually
+    // signed must be the same one: without this, a legitimately-signed
+    // `multi_signature` for one ledger state could be paired with a
+    // forged `root_hash` (and matching forged `proof_nodes`) for a
+    // completely different, attacker-chosen value, and `verify_inclusion`
+    // below would have no way to notice.
+    if state_proof.root_hash != state_proof.multi_signature.value.state_root_hash {
+        return Err(IndyError::from_msg(
+            IndyErrorKind::InvalidState,
+            format!(
+                "state_proof.root_hash ({}) does not match the root the validators signed \
+                 (multi_signature.value.state_root_hash {})",
+                state_proof.root_hash, state_proof.multi_signature.value.state_root_hash
+            ),
+        ));
+    }
+
+    let included = verify_inclusion(
+        &state_proof.proof_nodes,
+        &state_proof.root_hash,
+        key,
+        expected_value,
+    )?;
+
+    if !included {
+        return Err(IndyError::from_msg(
+            IndyErrorKind::InvalidState,
+            "state_proof does not include the expected value at the expected key",
+        ));
+    }
+
+    verify_multi_signature(&state_proof.multi_signature, validators)
+}
+
+/// Generic BFT-quorum check for a `REPLY`'s `state_proof`, suitable for
+/// `parse_response` to invoke regardless of the specific `GET_*` txn type:
+/// the aggregate BLS signature over `multi_signature.value` must carry at
+/// least `2f+1` of the `PoolValidators`' participants, and `value.timestamp`
+/// must fall within the configured trusting period of `now` — a reply
+/// signed further in the past than that is treated as stale rather than
+/// trusted, the same way a Tendermint light client rejects an expired
+/// header. This does not re-walk the Merkle-Patricia trie: per-field
+/// inclusion of a specific `(key, value)` is still `verify_state_proof`'s
+/// job, since only the caller building that key/value pair knows the GET
+/// type's encoding.
+pub fn verify_reply_proof(
+    state_proof: &StateProof,
+    validators: &PoolValidators,
+    now: u64,
+) -> IndyResult<()> {
+    let age = now.saturating_sub(state_proof.multi_signature.value.timestamp);
+    if age > validators.trusting_period_seconds {
+        return Err(IndyError::from_msg(
+            IndyErrorKind::InvalidState,
+            format!(
+                "state_proof timestamp {} is outside the trusting period ({}s old, max {}s)",
+                state_proof.multi_signature.value.timestamp, age, validators.trusting_period_seconds
+            ),
+        ));
+    }
+
+    verify_multi_signature_with_threshold(
+        &state_proof.multi_signature,
+        &validators.validators.blskeys,
+        &validators.validators.generator,
+        validators.minimum_quorum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hex-prefix (HP) encode `path` the way real MPT proof nodes do, so
+    /// test fixtures exercise the same encoding `decode_hex_prefix_path`
+    /// has to undo, rather than raw nibbles.
+    fn hex_prefix_encode(path: &[u8]) -> Vec<u8> {
+        let mut nibbles = Vec::with_capacity(path.len() + 2);
+        let is_odd = path.len() % 2 == 1;
+        nibbles.push(if is_odd { 1 } else { 0 });
+        if !is_odd {
+            nibbles.push(0);
+        }
+        nibbles.extend_from_slice(path);
+
+        nibbles
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect()
+    }
+
+    fn leaf_node(path: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut stream = rlp::RlpStream::new_list(2);
+        stream.append(&hex_prefix_encode(path));
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    #[test]
+    fn decode_hex_prefix_path_round_trips_odd_and_even_length_paths() {
+        assert_eq!(decode_hex_prefix_path(&hex_prefix_encode(&[1, 2, 3])), vec![1, 2, 3]);
+        assert_eq!(decode_hex_prefix_path(&hex_prefix_encode(&[1, 2, 3, 4])), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn step_trie_node_leaf_matches_when_path_equals_key_prefix() {
+        let mut nibbles = vec![1, 2, 3];
+        let node = leaf_node(&[1, 2, 3], b"value");
+
+        match step_trie_node(&node, &mut nibbles).unwrap() {
+            TrieStep::Leaf(value) => assert_eq!(b"value".to_vec(), value),
+            _ => panic!("expected a matching leaf node"),
+        }
+        assert!(nibbles.is_empty());
+    }
+
+    #[test]
+    fn step_trie_node_leaf_rejects_a_path_that_diverges_from_the_key() {
+        // The node's declared partial path ([9, 9, 3]) is not a prefix of
+        // the remaining key nibbles ([1, 2, 3]): this must not be treated
+        // as a match just because the lengths happen to line up.
+        let mut nibbles = vec![1, 2, 3];
+        let node = leaf_node(&[9, 9, 3], b"value");
+
+        let step = step_trie_node(&node, &mut nibbles).unwrap();
+        assert!(matches!(step, TrieStep::Empty));
+    }
+
+    #[test]
+    fn step_trie_node_leaf_rejects_a_path_longer_than_the_remaining_key() {
+        // A node whose declared path is longer than what's left of the key
+        // can never be a valid continuation of the walk, even though the
+        // shared prefix matches.
+        let mut nibbles = vec![1, 2];
+        let node = leaf_node(&[1, 2, 3], b"value");
+
+        let step = step_trie_node(&node, &mut nibbles).unwrap();
+        assert!(matches!(step, TrieStep::Empty));
+    }
+
+    #[test]
+    fn verify_inclusion_rejects_a_forged_single_node_proof_with_mismatched_path() {
+        let key = vec![0x12];
+        let node = leaf_node(&[9, 9], b"forged-value");
+        let root_hash = openssl_hash(&node).unwrap();
+        let proof_nodes = base64::encode(rlp::encode_list::<Vec<u8>, _>(&[node]));
+        let root_hash_b58 = bs58::encode(root_hash).into_string();
+
+        let included = verify_inclusion(&proof_nodes, &root_hash_b58, &key, b"forged-value").unwrap();
+        assert!(!included);
+    }
+
+    #[test]
+    fn verify_inclusion_accepts_a_genuine_single_leaf_proof() {
+        let key = vec![0x12];
+        let node = leaf_node(&[1, 2], b"real-value");
+        let root_hash = openssl_hash(&node).unwrap();
+        let proof_nodes = base64::encode(rlp::encode_list::<Vec<u8>, _>(&[node]));
+        let root_hash_b58 = bs58::encode(root_hash).into_string();
+
+        let included = verify_inclusion(&proof_nodes, &root_hash_b58, &key, b"real-value").unwrap();
+        assert!(included);
+    }
+
+    fn dummy_multi_signature(state_root_hash: &str) -> StateProofMultiSignature {
+        StateProofMultiSignature {
+            value: StateProofMultiSignatureValue {
+                ledger_id: 1,
+                state_root_hash: state_root_hash.to_string(),
+                pool_state_root_hash: "pool-root".to_string(),
+                txn_root_hash: "txn-root".to_string(),
+                timestamp: 0,
+            },
+            signature: "deadbeef".to_string(),
+            participants: vec![],
+        }
+    }
+
+    #[test]
+    fn verify_state_proof_rejects_a_root_hash_not_matching_what_the_validators_signed() {
+        // `root_hash` and `multi_signature.value.state_root_hash` disagree,
+        // so this must be rejected before the (otherwise irrelevant here)
+        // trie walk or BLS signature are even consulted.
+        let state_proof = StateProof {
+            root_hash: "forged-root".to_string(),
+            proof_nodes: base64::encode(rlp::encode_list::<Vec<u8>, Vec<u8>>(&[])),
+            multi_signature: dummy_multi_signature("signed-root"),
+        };
+
+        let validators = ValidatorSet::new(HashMap::new(), Generator::new().unwrap());
+        let err = verify_state_proof(&state_proof, b"key", b"value", &validators).unwrap_err();
+        assert!(err.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn minimum_honest_signers_does_not_panic_on_an_empty_validator_set() {
+        let validators = ValidatorSet::new(HashMap::new(), Generator::new().unwrap());
+        assert_eq!(validators.minimum_honest_signers(), 0);
+    }
+
+    #[test]
+    fn verify_multi_signature_accepts_a_genuine_aggregate_signature_over_the_genesis_generator() {
+        use ursa::bls::{Bls, SignKey};
+
+        // The generator every validator's verkey (and therefore the
+        // aggregate signature) was derived against: a mismatched generator
+        // must never verify, which is exactly the bug this test guards.
+        let generator = Generator::new().unwrap();
+
+        let sign_key_1 = SignKey::new(None).unwrap();
+        let ver_key_1 = VerKey::new(&generator, &sign_key_1).unwrap();
+        let sign_key_2 = SignKey::new(None).unwrap();
+        let ver_key_2 = VerKey::new(&generator, &sign_key_2).unwrap();
+
+        let mut blskeys = HashMap::new();
+        blskeys.insert("node1".to_string(), ver_key_1);
+        blskeys.insert("node2".to_string(), ver_key_2);
+
+        let value = StateProofMultiSignatureValue {
+            ledger_id: 1,
+            state_root_hash: "root".to_string(),
+            pool_state_root_hash: "pool-root".to_string(),
+            txn_root_hash: "txn-root".to_string(),
+            timestamp: 0,
+        };
+        let signed_bytes = serialize_signature(serde_json::to_value(&value).unwrap())
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+
+        let signature_1 = Bls::sign(&signed_bytes, &sign_key_1).unwrap();
+        let signature_2 = Bls::sign(&signed_bytes, &sign_key_2).unwrap();
+        let multi_signature_bytes = MultiSignature::new(&[signature_1, signature_2])
+            .unwrap()
+            .as_bytes()
+            .unwrap();
+
+        let multi_signature = StateProofMultiSignature {
+            value,
+            signature: hex::encode(multi_signature_bytes),
+            participants: vec!["node1".to_string(), "node2".to_string()],
+        };
+
+        let validators = ValidatorSet::new(blskeys, generator);
+        verify_multi_signature(&multi_signature, &validators).unwrap();
+    }
+
+    #[test]
+    fn verify_multi_signature_rejects_a_genuine_signature_checked_against_the_wrong_generator() {
+        use ursa::bls::{Bls, SignKey};
+
+        let generator = Generator::new().unwrap();
+        let wrong_generator = Generator::new().unwrap();
+
+        let sign_key = SignKey::new(None).unwrap();
+        let ver_key = VerKey::new(&generator, &sign_key).unwrap();
+
+        let mut blskeys = HashMap::new();
+        blskeys.insert("node1".to_string(), ver_key);
+
+        let value = StateProofMultiSignatureValue {
+            ledger_id: 1,
+            state_root_hash: "root".to_string(),
+            pool_state_root_hash: "pool-root".to_string(),
+            txn_root_hash: "txn-root".to_string(),
+            timestamp: 0,
+        };
+        let signed_bytes = serialize_signature(serde_json::to_value(&value).unwrap())
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+
+        let signature = Bls::sign(&signed_bytes, &sign_key).unwrap();
+        let multi_signature_bytes = MultiSignature::new(&[signature]).unwrap().as_bytes().unwrap();
+
+        let multi_signature = StateProofMultiSignature {
+            value,
+            signature: hex::encode(multi_signature_bytes),
+            participants: vec!["node1".to_string()],
+        };
+
+        // The verkey was derived against `generator`, not `wrong_generator`:
+        // checking it against the wrong generator must fail closed.
+        let validators = ValidatorSet::new(blskeys, wrong_generator);
+        assert!(verify_multi_signature(&multi_signature, &validators).is_err());
+    }
+
+    #[test]
+    fn minimum_quorum_does_not_panic_on_an_empty_validator_set() {
+        let pool_validators = PoolValidators::new(
+            ValidatorSet::new(HashMap::new(), Generator::new().unwrap()),
+            600,
+        );
+        assert_eq!(pool_validators.minimum_quorum(), 0);
+    }
+
+    #[test]
+    fn verify_reply_proof_rejects_a_genuine_signature_checked_against_the_wrong_generator() {
+        use ursa::bls::{Bls, SignKey};
+
+        // Same bug as `verify_multi_signature`'s generator check, exercised
+        // through the reply-quorum path `verify_reply_proof` actually uses.
+        let generator = Generator::new().unwrap();
+        let wrong_generator = Generator::new().unwrap();
+
+        let sign_key = SignKey::new(None).unwrap();
+        let ver_key = VerKey::new(&generator, &sign_key).unwrap();
+
+        let mut blskeys = HashMap::new();
+        blskeys.insert("node1".to_string(), ver_key);
+
+        let value = StateProofMultiSignatureValue {
+            ledger_id: 1,
+            state_root_hash: "root".to_string(),
+            pool_state_root_hash: "pool-root".to_string(),
+            txn_root_hash: "txn-root".to_string(),
+            timestamp: 0,
+        };
+        let signed_bytes = serialize_signature(serde_json::to_value(&value).unwrap())
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+
+        let signature = Bls::sign(&signed_bytes, &sign_key).unwrap();
+        let multi_signature_bytes = MultiSignature::new(&[signature]).unwrap().as_bytes().unwrap();
+
+        let state_proof = StateProof {
+            root_hash: "root".to_string(),
+            proof_nodes: base64::encode(rlp::encode_list::<Vec<u8>, Vec<u8>>(&[])),
+            multi_signature: StateProofMultiSignature {
+                value,
+                signature: hex::encode(multi_signature_bytes),
+                participants: vec!["node1".to_string()],
+            },
+        };
+
+        let pool_validators = PoolValidators::new(ValidatorSet::new(blskeys, wrong_generator), 600);
+        let err = verify_reply_proof(&state_proof, &pool_validators, 0).unwrap_err();
+        assert!(err.to_string().contains("did not verify"));
+    }
+}
@@ -1,12 +1,22 @@
 pub mod merkletree;
+pub mod tails;
+#[cfg(feature = "otel_metrics")]
+pub mod telemetry;
 
+use self::merkletree::{verify_state_proof, PoolValidators, StateProof, ValidatorSet};
 use hex::FromHex;
+use std::collections::HashSet;
+use std::sync::RwLock;
 use indy_api_types::errors::prelude::*;
 use indy_utils::crypto::hash::hash as openssl_hash;
 use log_derive::logfn;
+use openssl::hash::{Hasher, MessageDigest};
 use serde::de::DeserializeOwned;
 use serde_json::{self, Value};
-use ursa::cl::RevocationRegistryDelta as CryproRevocationRegistryDelta;
+use ursa::cl::{
+    RevocationRegistry as CryptoRevocationRegistry,
+    RevocationRegistryDelta as CryproRevocationRegistryDelta,
+};
 
 use crate::{
     domain::{
@@ -43,8 +53,8 @@ use crate::{
             },
             rev_reg_def::{GetRevRegDefOperation, GetRevocRegDefReplyResult, RevRegDefOperation},
             schema::{
-                GetSchemaOperation, GetSchemaOperationData, GetSchemaReplyResult, SchemaOperation,
-                SchemaOperationData,
+                GetSchemaOperation, GetSchemaOperationData, GetSchemaReplyResult, GetTxnResultData,
+                SchemaOperation, SchemaOperationData,
             },
             txn::{GetTxnOperation, LedgerType},
             validator_info::GetValidatorInfoOperation,
@@ -55,24 +65,222 @@ use crate::{
 
 macro_rules! build_result {
         ($operation:ident, $submitter_did:expr) => ({
-            let operation = $operation::new();
+            self.instrument_build(stringify!($operation), || {
+                let operation = $operation::new();
 
-            Request::build_request($submitter_did, operation)
-                .map_err(|err| IndyError::from_msg(IndyErrorKind::InvalidState, err))
+                Request::build_request($submitter_did, operation)
+                    .map_err(|err| IndyError::from_msg(IndyErrorKind::InvalidState, err))
+            })
         });
         ($operation:ident, $submitter_did:expr, $($params:tt)*) => ({
-            let operation = $operation::new($($params)*);
+            self.instrument_build(stringify!($operation), || {
+                let operation = $operation::new($($params)*);
 
-            Request::build_request($submitter_did, operation)
-                .map_err(|err| IndyError::from_msg(IndyErrorKind::InvalidState, err))
+                Request::build_request($submitter_did, operation)
+                    .map_err(|err| IndyError::from_msg(IndyErrorKind::InvalidState, err))
+            })
         })
     }
 
-pub(crate) struct LedgerService {}
+/// Loosely-typed mirror of the wire shape `CryproRevocationRegistryDelta`
+/// serializes to, used to inspect/merge deltas without depending on ursa's
+/// internal representation.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevocationRegistryDeltaValueJson {
+    #[serde(default)]
+    prev_accum: Option<serde_json::Value>,
+    accum: Option<serde_json::Value>,
+    #[serde(default)]
+    issued: HashSet<u32>,
+    #[serde(default)]
+    revoked: HashSet<u32>,
+}
+
+/// A TAA fetched via `build_get_txn_author_agreement_request` and parsed by
+/// the caller, kept around so `auto_append_acceptance` doesn't need the
+/// get/parse dance re-run on every write.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedTaa {
+    pub text: String,
+    pub version: String,
+    pub ratification_ts: Option<u64>,
+    pub retirement_ts: Option<u64>,
+}
+
+/// Per-ledger cache of the current TAA and Acceptance Mechanisms List.
+/// `LedgerService` itself never performs the network fetch (it only
+/// builds/parses requests, like everything else here); callers populate the
+/// cache once via `set_cached_taa`/`set_cached_aml` after submitting the corresponding
+/// `GET_TXN_AUTHR_AGRMT`/`GET_ACCEPTANCE_MECHANISMS` requests, and
+/// `auto_append_acceptance` reads from it on every subsequent write.
+#[derive(Default)]
+struct TaaCache {
+    taa: RwLock<HashMap<String, CachedTaa>>,
+    aml: RwLock<HashMap<String, AcceptanceMechanisms>>,
+}
+
+impl TaaCache {
+    fn taa_for(&self, ledger_id: &str) -> Option<CachedTaa> {
+        self.taa.read().unwrap().get(ledger_id).cloned()
+    }
+
+    fn mechanism_known(&self, ledger_id: &str, mechanism: &str) -> bool {
+        self.aml
+            .read()
+            .unwrap()
+            .get(ledger_id)
+            .map(|aml| aml.0.contains_key(mechanism))
+            .unwrap_or(false)
+    }
+
+    fn sole_mechanism(&self, ledger_id: &str) -> Option<String> {
+        let aml = self.aml.read().unwrap();
+        let mechanisms = aml.get(ledger_id)?;
+        match mechanisms.0.keys().collect::<Vec<_>>().as_slice() {
+            [only] => Some((*only).clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Digest algorithm used for a TAA acceptance digest (and to verify one
+/// passed in by a caller). `Sha256` matches `_calculate_hash`'s previous
+/// hardcoded behavior and stays the default so existing callers see no
+/// change in the `taa_digest` they get back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DigestAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_256,
+}
+
+impl Default for DigestAlgorithm {
+    fn default() -> Self {
+        DigestAlgorithm::Sha256
+    }
+}
+
+impl DigestAlgorithm {
+    fn message_digest(self) -> MessageDigest {
+        match self {
+            DigestAlgorithm::Sha256 => MessageDigest::sha256(),
+            DigestAlgorithm::Sha384 => MessageDigest::sha384(),
+            DigestAlgorithm::Sha512 => MessageDigest::sha512(),
+            DigestAlgorithm::Sha3_256 => MessageDigest::sha3_256(),
+        }
+    }
+
+    fn hash(self, content: &[u8]) -> IndyResult<Vec<u8>> {
+        if self == DigestAlgorithm::Sha256 {
+            // `openssl_hash` is the helper the rest of the crate already
+            // uses for SHA-256; keep using it rather than going through
+            // `openssl::hash::Hasher` twice for the default case.
+            return openssl_hash(content);
+        }
+
+        let mut hasher = Hasher::new(self.message_digest())
+            .to_indy(IndyErrorKind::InvalidState, "Cannot initialize digest hasher")?;
+        hasher
+            .update(content)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot hash content")?;
+        let digest = hasher
+            .finish()
+            .to_indy(IndyErrorKind::InvalidState, "Cannot finalize digest hash")?;
+        Ok(digest.to_vec())
+    }
+}
+
+/// Pool protocol version the indy-node ledger spoke before `GET_TXN`'s
+/// `ledgerId` argument and the TAA-acceptance mechanism were introduced.
+/// Request builders fall back to this shape when `LedgerService` is
+/// pinned to it via `set_protocol_version`.
+const LEGACY_PROTOCOL_VERSION: u64 = 1;
+
+/// Current pool protocol version, and the default a fresh `LedgerService`
+/// builds requests for.
+const CURRENT_PROTOCOL_VERSION: u64 = 2;
+
+pub(crate) struct LedgerService {
+    #[cfg(feature = "otel_metrics")]
+    metrics: telemetry::LedgerMetrics,
+    taa_cache: TaaCache,
+    protocol_version: RwLock<u64>,
+}
 
 impl LedgerService {
     pub(crate) fn new() -> LedgerService {
-        LedgerService {}
+        LedgerService {
+            #[cfg(feature = "otel_metrics")]
+            metrics: telemetry::LedgerMetrics::new(),
+            taa_cache: TaaCache::default(),
+            protocol_version: RwLock::new(CURRENT_PROTOCOL_VERSION),
+        }
+    }
+
+    /// Pin request formatting to `version` so requests built for an
+    /// older pool (one that predates the current `ledgerId`/TAA argument
+    /// shapes) come out in the form that pool expects.
+    pub(crate) fn set_protocol_version(&self, version: u64) {
+        *self.protocol_version.write().unwrap() = version;
+    }
+
+    pub(crate) fn protocol_version(&self) -> u64 {
+        *self.protocol_version.read().unwrap()
+    }
+
+    /// Cache the TAA fetched for `ledger_id`, replacing whatever was there
+    /// before. `ledger_id` lets a multi-ledger pool config keep each
+    /// ledger's TAA separate rather than assuming a single global one.
+    pub(crate) fn set_cached_taa(&self, ledger_id: &str, taa: CachedTaa) {
+        self.taa_cache
+            .taa
+            .write()
+            .unwrap()
+            .insert(ledger_id.to_string(), taa);
+    }
+
+    /// Cache the Acceptance Mechanisms List fetched for `ledger_id`.
+    pub(crate) fn set_cached_aml(&self, ledger_id: &str, aml: AcceptanceMechanisms) {
+        self.taa_cache
+            .aml
+            .write()
+            .unwrap()
+            .insert(ledger_id.to_string(), aml);
+    }
+
+    /// Run `f` (a `build_*` call) inside an OpenTelemetry span/timer tagged
+    /// with its txn type when the `otel_metrics` feature is enabled, so the
+    /// span/duration actually cover the call instead of being recorded
+    /// after it already finished; a no-op passthrough otherwise so the
+    /// crate builds without the extra dependencies.
+    #[cfg(feature = "otel_metrics")]
+    fn instrument_build<T>(&self, txn_type: &str, f: impl FnOnce() -> IndyResult<T>) -> IndyResult<T> {
+        let mut span = telemetry::TxnSpan::start("ledger.build", txn_type, &self.metrics);
+        let result = f();
+        span.record_built();
+        result
+    }
+
+    #[cfg(not(feature = "otel_metrics"))]
+    fn instrument_build<T>(&self, _txn_type: &str, f: impl FnOnce() -> IndyResult<T>) -> IndyResult<T> {
+        f()
+    }
+
+    /// Same as `instrument_build`, but for `parse_*` calls: records a
+    /// success/failure counter keyed by txn type instead of a build count.
+    #[cfg(feature = "otel_metrics")]
+    fn instrument_parse<T>(&self, txn_type: &str, f: impl FnOnce() -> IndyResult<T>) -> IndyResult<T> {
+        let mut span = telemetry::TxnSpan::start("ledger.parse", txn_type, &self.metrics);
+        let result = f();
+        span.record_parse_result(result.is_ok());
+        result
+    }
+
+    #[cfg(not(feature = "otel_metrics"))]
+    fn instrument_parse<T>(&self, _txn_type: &str, f: impl FnOnce() -> IndyResult<T>) -> IndyResult<T> {
+        f()
     }
 
     #[logfn(Info)]
@@ -124,9 +332,84 @@ impl LedgerService {
         build_result!(GetNymOperation, identifier, dest.to_short())
     }
 
+    /// Verify the `state_proof` a ledger read reply carries before trusting
+    /// its `result`: the Merkle-Patricia-Trie inclusion proof of `(key,
+    /// expected_value)` under the proof's root, and the BLS multi-signature
+    /// over that root. Opt-in: callers that don't pass `validators` get the
+    /// previous trust-on-read behavior unchanged.
+    fn verify_read_state_proof(
+        response: &str,
+        key: &[u8],
+        expected_value: &[u8],
+        validators: Option<&ValidatorSet>,
+    ) -> IndyResult<()> {
+        let validators = match validators {
+            Some(validators) => validators,
+            None => return Ok(()),
+        };
+
+        let message: Value = serde_json::from_str(response).to_indy(
+            IndyErrorKind::InvalidTransaction,
+            "Response is invalid json",
+        )?;
+
+        let state_proof = message["result"]["state_proof"].clone();
+        if state_proof.is_null() {
+            return Err(IndyError::from_msg(
+                IndyErrorKind::InvalidState,
+                "Ledger reply does not carry a state_proof to verify",
+            ));
+        }
+
+        let state_proof = serde_json::from_value(state_proof).to_indy(
+            IndyErrorKind::InvalidState,
+            "Cannot parse state_proof",
+        )?;
+
+        verify_state_proof(&state_proof, key, expected_value, validators)
+    }
+
+    /// Generic BFT-quorum + trusting-period check `parse_response` can run
+    /// against any `REPLY`'s `state_proof`, independent of the GET txn type
+    /// (see `merkletree::verify_reply_proof` for what it does and does not
+    /// check). `now` is the caller's current time, following the same
+    /// explicit-clock convention as `prepare_acceptance_data`'s `time`
+    /// parameter rather than reading the wall clock here.
+    #[logfn(Info)]
+    pub(crate) fn verify_reply_proof(
+        &self,
+        response: &str,
+        validators: &PoolValidators,
+        now: u64,
+    ) -> IndyResult<()> {
+        let message: Value = serde_json::from_str(response).to_indy(
+            IndyErrorKind::InvalidTransaction,
+            "Response is invalid json",
+        )?;
+
+        let state_proof = message["result"]["state_proof"].clone();
+        if state_proof.is_null() {
+            return Err(IndyError::from_msg(
+                IndyErrorKind::InvalidState,
+                "Ledger reply does not carry a state_proof to verify",
+            ));
+        }
+
+        let state_proof: StateProof = serde_json::from_value(state_proof).to_indy(
+            IndyErrorKind::InvalidState,
+            "Cannot parse state_proof",
+        )?;
+
+        self::merkletree::verify_reply_proof(&state_proof, validators, now)
+    }
+
     #[logfn(Info)]
-    pub(crate) fn parse_get_nym_response(&self, get_nym_response: &str) -> IndyResult<String> {
-        let reply: Reply<GetNymReplyResult> = LedgerService::parse_response(get_nym_response)?;
+    pub(crate) fn parse_get_nym_response(
+        &self,
+        get_nym_response: &str,
+        validators: Option<&ValidatorSet>,
+    ) -> IndyResult<String> {
+        let reply: Reply<GetNymReplyResult> = self.parse_response_instrumented(get_nym_response)?;
 
         let nym_data = match reply.result() {
             GetNymReplyResult::GetNymReplyResultV0(res) => {
@@ -165,6 +448,13 @@ impl LedgerService {
             )
         })?;
 
+        LedgerService::verify_read_state_proof(
+            get_nym_response,
+            nym_data.did.0.as_bytes(),
+            res.as_bytes(),
+            validators,
+        )?;
+
         Ok(res)
     }
 
@@ -327,9 +617,38 @@ impl LedgerService {
             None => LedgerType::DOMAIN.to_id(),
         };
 
+        if self.protocol_version() == LEGACY_PROTOCOL_VERSION {
+            if ledger_type.is_some() && ledger_id != LedgerType::DOMAIN.to_id() {
+                return Err(IndyError::from_msg(
+                    IndyErrorKind::InvalidStructure,
+                    "GET_TXN ledger type selection requires protocol version 2 or higher",
+                ));
+            }
+
+            let request = build_result!(GetTxnOperation, identifier, seq_no, ledger_id)?;
+            return Self::remove_operation_field(&request, "ledgerId");
+        }
+
         build_result!(GetTxnOperation, identifier, seq_no, ledger_id)
     }
 
+    /// Drop `field` from the built request's `operation` object, for
+    /// callers that need a request shaped for a pool protocol version
+    /// predating that field. The operation struct itself always emits
+    /// the current field set; this trims it back down after the fact
+    /// rather than threading version checks through every operation type.
+    fn remove_operation_field(request: &str, field: &str) -> IndyResult<String> {
+        let mut value: Value = serde_json::from_str(request)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot deserialize request")?;
+
+        if let Some(operation) = value.get_mut("operation").and_then(Value::as_object_mut) {
+            operation.remove(field);
+        }
+
+        serde_json::to_string(&value)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize request")
+    }
+
     #[logfn(Info)]
     pub(crate) fn build_pool_config(
         &self,
@@ -459,9 +778,10 @@ impl LedgerService {
         &self,
         get_schema_response: &str,
         method_name: Option<&str>,
+        validators: Option<&ValidatorSet>,
     ) -> IndyResult<(String, String)> {
         let reply: Reply<GetSchemaReplyResult> =
-            LedgerService::parse_response(get_schema_response)?;
+            self.parse_response_instrumented(get_schema_response)?;
 
         let schema = match reply.result() {
             GetSchemaReplyResult::GetSchemaReplyResultV0(res) => SchemaV1 {
@@ -493,6 +813,56 @@ impl LedgerService {
                 .to_indy(IndyErrorKind::InvalidState, "Cannot serialize Schema")?,
         );
 
+        LedgerService::verify_read_state_proof(
+            get_schema_response,
+            res.0.as_bytes(),
+            res.1.as_bytes(),
+            validators,
+        )?;
+
+        Ok(res)
+    }
+
+    /// Resolve a schema from a `GET_TXN` reply for its ledger sequence
+    /// number, rather than the `dest` + name/version `GET_SCHEMA` needs —
+    /// the path a cred-def's `schemaId`-as-seqno takes. This is a
+    /// separate `Reply<GetTxnResultData>` parse (its own `GET_TXN` type
+    /// tag) rather than a `GetSchemaReplyResult` variant, since that
+    /// enum's `ReplyType::get_type()` always checks for `GET_SCHEMA`.
+    #[logfn(Info)]
+    pub(crate) fn parse_get_txn_schema_response(
+        &self,
+        get_txn_response: &str,
+        method_name: Option<&str>,
+        validators: Option<&ValidatorSet>,
+    ) -> IndyResult<(String, String)> {
+        let reply: Reply<GetTxnResultData> = self.parse_response_instrumented(get_txn_response)?;
+
+        let result = reply.result();
+        let seq_no = result.seq_no;
+        let (from, data) = result.into_schema_data();
+
+        let schema = SchemaV1 {
+            id: SchemaId::new(&DidValue::new(&from.0, None, method_name)?, &data.name, &data.version)?,
+            name: data.name,
+            version: data.version,
+            attr_names: data.attr_names.into(),
+            seq_no: Some(seq_no),
+        };
+
+        let res = (
+            schema.id.0.clone(),
+            serde_json::to_string(&Schema::SchemaV1(schema))
+                .to_indy(IndyErrorKind::InvalidState, "Cannot serialize Schema")?,
+        );
+
+        LedgerService::verify_read_state_proof(
+            get_txn_response,
+            res.0.as_bytes(),
+            res.1.as_bytes(),
+            validators,
+        )?;
+
         Ok(res)
     }
 
@@ -501,9 +871,10 @@ impl LedgerService {
         &self,
         get_cred_def_response: &str,
         method_name: Option<&str>,
+        validators: Option<&ValidatorSet>,
     ) -> IndyResult<(String, String)> {
         let reply: Reply<GetCredDefReplyResult> =
-            LedgerService::parse_response(get_cred_def_response)?;
+            self.parse_response_instrumented(get_cred_def_response)?;
 
         let cred_def = match reply.result() {
             GetCredDefReplyResult::GetCredDefReplyResultV0(res) => CredentialDefinitionV1 {
@@ -539,6 +910,13 @@ impl LedgerService {
                 )?,
         );
 
+        LedgerService::verify_read_state_proof(
+            get_cred_def_response,
+            res.0.as_bytes(),
+            res.1.as_bytes(),
+            validators,
+        )?;
+
         Ok(res)
     }
 
@@ -546,9 +924,10 @@ impl LedgerService {
     pub(crate) fn parse_get_revoc_reg_def_response(
         &self,
         get_revoc_reg_def_response: &str,
+        validators: Option<&ValidatorSet>,
     ) -> IndyResult<(String, String)> {
         let reply: Reply<GetRevocRegDefReplyResult> =
-            LedgerService::parse_response(get_revoc_reg_def_response)?;
+            self.parse_response_instrumented(get_revoc_reg_def_response)?;
 
         let revoc_reg_def = match reply.result() {
             GetRevocRegDefReplyResult::GetRevocRegDefReplyResultV0(res) => res.data,
@@ -566,6 +945,13 @@ impl LedgerService {
             )?,
         );
 
+        LedgerService::verify_read_state_proof(
+            get_revoc_reg_def_response,
+            res.0.as_bytes(),
+            res.1.as_bytes(),
+            validators,
+        )?;
+
         Ok(res)
     }
 
@@ -573,9 +959,10 @@ impl LedgerService {
     pub(crate) fn parse_get_revoc_reg_response(
         &self,
         get_revoc_reg_response: &str,
+        validators: Option<&ValidatorSet>,
     ) -> IndyResult<(String, String, u64)> {
         let reply: Reply<GetRevocRegReplyResult> =
-            LedgerService::parse_response(get_revoc_reg_response)?;
+            self.parse_response_instrumented(get_revoc_reg_response)?;
 
         let (revoc_reg_def_id, revoc_reg, txn_time) = match reply.result() {
             GetRevocRegReplyResult::GetRevocRegReplyResultV0(res) => {
@@ -597,6 +984,13 @@ impl LedgerService {
             txn_time,
         );
 
+        LedgerService::verify_read_state_proof(
+            get_revoc_reg_response,
+            res.0.as_bytes(),
+            res.1.as_bytes(),
+            validators,
+        )?;
+
         Ok(res)
     }
 
@@ -604,9 +998,10 @@ impl LedgerService {
     pub(crate) fn parse_get_revoc_reg_delta_response(
         &self,
         get_revoc_reg_delta_response: &str,
+        validators: Option<&ValidatorSet>,
     ) -> IndyResult<(String, String, u64)> {
         let reply: Reply<GetRevocRegDeltaReplyResult> =
-            LedgerService::parse_response(get_revoc_reg_delta_response)?;
+            self.parse_response_instrumented(get_revoc_reg_delta_response)?;
 
         let (revoc_reg_def_id, revoc_reg) = match reply.result() {
             GetRevocRegDeltaReplyResult::GetRevocRegDeltaReplyResultV0(res) => {
@@ -636,9 +1031,141 @@ impl LedgerService {
             revoc_reg.value.accum_to.txn_time,
         );
 
+        LedgerService::verify_read_state_proof(
+            get_revoc_reg_delta_response,
+            res.0.as_bytes(),
+            res.1.as_bytes(),
+            validators,
+        )?;
+
         Ok(res)
     }
 
+    /// Compose two consecutive revocation registry deltas (T0->T1 and
+    /// T1->T2, as serialized by `parse_get_revoc_reg_delta_response`) into a
+    /// single T0->T2 delta, without a ledger round-trip over the full range.
+    /// See `check_delta_contiguity`/`merge_issued_revoked` for how the two
+    /// halves are validated and combined.
+    #[logfn(Info)]
+    pub(crate) fn merge_revoc_reg_deltas(
+        &self,
+        delta_a: &str,
+        delta_b: &str,
+    ) -> IndyResult<String> {
+        let delta_a: RevocationRegistryDeltaValueJson = Self::parse_delta_value(delta_a)?;
+        let delta_b: RevocationRegistryDeltaValueJson = Self::parse_delta_value(delta_b)?;
+
+        Self::check_delta_contiguity(&delta_a, &delta_b)?;
+
+        let (issued, revoked) = Self::merge_issued_revoked(&delta_a, &delta_b);
+
+        let prev_accum = delta_a
+            .prev_accum
+            .map(Self::parse_accum)
+            .transpose()?;
+
+        let accum_to = delta_b
+            .accum
+            .ok_or(IndyError::from_msg(
+                IndyErrorKind::InvalidStructure,
+                "delta_b is missing its `accum` value",
+            ))
+            .and_then(Self::parse_accum)?;
+
+        let merged = CryproRevocationRegistryDelta::from_parts(
+            prev_accum.as_ref(),
+            &accum_to,
+            &issued,
+            &revoked,
+        );
+
+        serde_json::to_string(&RevocationRegistryDelta::RevocationRegistryDeltaV1(
+            RevocationRegistryDeltaV1 { value: merged },
+        ))
+        .to_indy(
+            IndyErrorKind::InvalidState,
+            "Cannot serialize merged RevocationRegistryDelta",
+        )
+    }
+
+    /// `delta_b` must pick up exactly where `delta_a` left off: its
+    /// `prev_accum` must equal `delta_a`'s `accum`. `delta_b.prev_accum` is
+    /// only absent for a from-genesis delta (nothing before it), so that
+    /// case requires `delta_a` to likewise have no accumulator yet, rather
+    /// than skipping the check.
+    fn check_delta_contiguity(
+        delta_a: &RevocationRegistryDeltaValueJson,
+        delta_b: &RevocationRegistryDeltaValueJson,
+    ) -> IndyResult<()> {
+        match (&delta_b.prev_accum, &delta_a.accum) {
+            (Some(accum_from_b), accum_to_a) if Some(accum_from_b) == accum_to_a.as_ref() => Ok(()),
+            (None, None) => Ok(()),
+            _ => Err(err_msg(
+                IndyErrorKind::InvalidStructure,
+                "Cannot merge non-contiguous revocation registry deltas: \
+                 delta_b's accum_from does not equal delta_a's accum_to",
+            )),
+        }
+    }
+
+    /// `issued`/`revoked` are combined with symmetric set algebra so that an
+    /// index revoked in one half and (re-)issued in the other cancels out
+    /// and appears in neither set, rather than in both:
+    /// `issued = (issued_a \ revoked_b) ∪ (issued_b \ revoked_a)`,
+    /// `revoked = (revoked_a \ issued_b) ∪ (revoked_b \ issued_a)`.
+    fn merge_issued_revoked(
+        delta_a: &RevocationRegistryDeltaValueJson,
+        delta_b: &RevocationRegistryDeltaValueJson,
+    ) -> (HashSet<u32>, HashSet<u32>) {
+        let issued = delta_a
+            .issued
+            .difference(&delta_b.revoked)
+            .chain(delta_b.issued.difference(&delta_a.revoked))
+            .copied()
+            .collect::<HashSet<u32>>();
+
+        let revoked = delta_a
+            .revoked
+            .difference(&delta_b.issued)
+            .chain(delta_b.revoked.difference(&delta_a.issued))
+            .copied()
+            .collect::<HashSet<u32>>();
+
+        (issued, revoked)
+    }
+
+    /// `RevocationRegistryDeltaValueJson` keeps `accum`/`prev_accum` as
+    /// opaque `serde_json::Value`s so contiguity checking doesn't need to
+    /// know anything about ursa's internal representation; this wraps one of
+    /// those bare accumulator values back into the `{"accum": ...}` registry
+    /// type `from_parts` requires, once an accumulator is actually about to
+    /// be merged.
+    fn parse_accum(accum: serde_json::Value) -> IndyResult<CryptoRevocationRegistry> {
+        serde_json::from_value(json!({ "accum": accum })).to_indy(
+            IndyErrorKind::InvalidStructure,
+            "Cannot parse revocation registry accumulator value",
+        )
+    }
+
+    fn parse_delta_value(delta: &str) -> IndyResult<RevocationRegistryDeltaValueJson> {
+        let delta: RevocationRegistryDelta = serde_json::from_str(delta).to_indy(
+            IndyErrorKind::InvalidStructure,
+            "Cannot parse RevocationRegistryDelta to merge",
+        )?;
+
+        let RevocationRegistryDelta::RevocationRegistryDeltaV1(delta) = delta;
+
+        let value = serde_json::to_value(&delta.value).to_indy(
+            IndyErrorKind::InvalidState,
+            "Cannot inspect RevocationRegistryDelta value",
+        )?;
+
+        serde_json::from_value(value).to_indy(
+            IndyErrorKind::InvalidState,
+            "Unexpected RevocationRegistryDelta value shape",
+        )
+    }
+
     #[logfn(Info)]
     pub(crate) fn build_auth_rule_request(
         &self,
@@ -842,6 +1369,16 @@ impl LedgerService {
         }
     }
 
+    /// `parse_response` tagged with an OpenTelemetry span/counter for `T`'s
+    /// txn type (see `telemetry`), used by every `parse_get_*` wrapper so
+    /// the instrumentation lives in one place rather than at each call site.
+    fn parse_response_instrumented<T>(&self, response: &str) -> IndyResult<Reply<T>>
+    where
+        T: DeserializeOwned + ReplyType + ::std::fmt::Debug,
+    {
+        self.instrument_parse(T::get_type(), || Self::parse_response::<T>(response))
+    }
+
     #[logfn(Info)]
     pub(crate) fn validate_action(&self, request: &str) -> IndyResult<()> {
         let request: Request<serde_json::Value> = serde_json::from_str(request).map_err(|err| {
@@ -872,6 +1409,7 @@ impl LedgerService {
         hash: Option<&str>,
         mechanism: &str,
         time: u64,
+        digest_algorithm: DigestAlgorithm,
     ) -> IndyResult<TxnAuthrAgrmtAcceptanceData> {
         let taa_digest = match (text, version, hash) {
             (None, None, None) => {
@@ -882,10 +1420,10 @@ impl LedgerService {
                 return Err(err_msg(IndyErrorKind::InvalidStructure, "Invalid combination of params: `text` and `version` should be passed or skipped together."));
             }
             (Some(text_), Some(version_), None) => {
-                hex::encode(self._calculate_hash(text_, version_)?)
+                hex::encode(self._calculate_hash(text_, version_, digest_algorithm)?)
             }
             (Some(text_), Some(version_), Some(hash_)) => {
-                self._compare_hash(text_, version_, hash_)?;
+                self._compare_hash(text_, version_, hash_, digest_algorithm)?;
                 hash_.to_string()
             }
         };
@@ -904,13 +1442,24 @@ impl LedgerService {
         time / SEC_IN_DAY * SEC_IN_DAY
     }
 
-    fn _calculate_hash(&self, text: &str, version: &str) -> IndyResult<Vec<u8>> {
+    fn _calculate_hash(
+        &self,
+        text: &str,
+        version: &str,
+        digest_algorithm: DigestAlgorithm,
+    ) -> IndyResult<Vec<u8>> {
         let content: String = version.to_string() + text;
-        openssl_hash(content.as_bytes())
+        digest_algorithm.hash(content.as_bytes())
     }
 
-    fn _compare_hash(&self, text: &str, version: &str, hash: &str) -> IndyResult<()> {
-        let calculated_hash = self._calculate_hash(text, version)?;
+    fn _compare_hash(
+        &self,
+        text: &str,
+        version: &str,
+        hash: &str,
+        digest_algorithm: DigestAlgorithm,
+    ) -> IndyResult<()> {
+        let calculated_hash = self._calculate_hash(text, version, digest_algorithm)?;
 
         let passed_hash = Vec::from_hex(hash).map_err(|err| {
             IndyError::from_msg(
@@ -982,58 +1531,298 @@ impl LedgerService {
         taa_digest: Option<&str>,
         acc_mech_type: &str,
         time: u64,
+        digest_algorithm: DigestAlgorithm,
     ) -> IndyResult<()> {
-        let taa_acceptance =
-            self.prepare_acceptance_data(text, version, taa_digest, &acc_mech_type, time)?;
+        if self.protocol_version() == LEGACY_PROTOCOL_VERSION {
+            return Err(IndyError::from_msg(
+                IndyErrorKind::InvalidState,
+                "Transaction Author Agreement acceptance requires protocol version 2 or higher",
+            ));
+        }
+
+        let taa_acceptance = self.prepare_acceptance_data(
+            text,
+            version,
+            taa_digest,
+            &acc_mech_type,
+            time,
+            digest_algorithm,
+        )?;
         transaction.taa_acceptance = Some(taa_acceptance);
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Transparently satisfy `transaction`'s TAA requirement from the TAA
+    /// and AML already cached for `ledger_id` (see `set_cached_taa`/
+    /// `set_cached_aml`), instead of every write manually re-running the
+    /// get/parse/append dance. `preferred_mechanism` is validated against
+    /// the cached AML and defaults to its only entry if the AML has just
+    /// one; the digest is computed via the usual `prepare_acceptance_data` /
+    /// `_calculate_hash` path. A TAA retired at `time` is rejected outright
+    /// rather than appended, so a stale cache can't silently produce a
+    /// write that the ledger will reject anyway.
+    #[logfn(Info)]
+    pub(crate) fn auto_append_acceptance(
+        &self,
+        ledger_id: &str,
+        transaction: &mut Request<serde_json::Value>,
+        preferred_mechanism: Option<&str>,
+        time: u64,
+    ) -> IndyResult<()> {
+        let taa = self.taa_cache.taa_for(ledger_id).ok_or_else(|| {
+            IndyError::from_msg(
+                IndyErrorKind::InvalidState,
+                format!(
+                    "No Transaction Author Agreement cached for ledger `{}`; call set_cached_taa first",
+                    ledger_id
+                ),
+            )
+        })?;
 
-    use crate::domain::{
-        anoncreds::schema::AttributeNames,
-        ledger::{constants::*, node::Services, request::ProtocolVersion},
-    };
+        if let Some(retirement_ts) = taa.retirement_ts {
+            if time >= retirement_ts {
+                return Err(IndyError::from_msg(
+                    IndyErrorKind::InvalidState,
+                    format!(
+                        "Cached TAA version `{}` was retired at {}; fetch and cache a current TAA before this write",
+                        taa.version, retirement_ts
+                    ),
+                ));
+            }
+        }
 
-    const IDENTIFIER: &str = "NcYxiDXkpYi6ov5FcYDi1e";
-    const DEST: &str = "VsKV7grR1BUE29mG2Fm2kX";
-    const VERKEY: &str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+        let mechanism = match preferred_mechanism {
+            Some(mechanism) => mechanism.to_string(),
+            None => self.taa_cache.sole_mechanism(ledger_id).ok_or_else(|| {
+                IndyError::from_msg(
+                    IndyErrorKind::InvalidStructure,
+                    "No acceptance mechanism specified and the cached AML does not have exactly one to default to",
+                )
+            })?,
+        };
 
-    fn identifier() -> DidValue {
-        DidValue(IDENTIFIER.to_string())
-    }
+        if !self.taa_cache.mechanism_known(ledger_id, &mechanism) {
+            return Err(IndyError::from_msg(
+                IndyErrorKind::InvalidStructure,
+                format!(
+                    "Acceptance mechanism `{}` is not present in the cached AML for ledger `{}`",
+                    mechanism, ledger_id
+                ),
+            ));
+        }
 
-    fn dest() -> DidValue {
-        DidValue(DEST.to_string())
+        self.append_txn_author_agreement_acceptance_to_request(
+            transaction,
+            Some(&taa.text),
+            Some(&taa.version),
+            None,
+            &mechanism,
+            time,
+            DigestAlgorithm::default(),
+        )
     }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+    /// Raw-JSON counterpart to `append_txn_endorser` for callers that only
+    /// hold the already-built request string (e.g. an author handing a
+    /// request off to a separate endorser process). Sets the `endorser`
+    /// field and strips the author's own single-signer `signature`, since an
+    /// endorsed request is submitted once both parties have multi-signed it.
+    pub(crate) fn append_request_endorser(
+        &self,
+        request: &str,
+        endorser_did: &DidValue,
+    ) -> IndyResult<String> {
+        let mut request: Value = serde_json::from_str(request).map_err(|err| {
+            IndyError::from_msg(
+                IndyErrorKind::InvalidStructure,
+                format!("Request is invalid json: {:?}", err),
+            )
+        })?;
 
-        #[async_std::test]
-        async fn ledger_service_allows_send() {
-            use futures::{channel::oneshot, executor::ThreadPool};
-            use std::sync::Arc;
+        if !request.is_object() {
+            return Err(err_msg(
+                IndyErrorKind::InvalidStructure,
+                "Unable to append endorser as request is not an object.",
+            ));
+        }
 
-            let executor = Arc::new(ThreadPool::new().expect("Failed to new ThreadPool"));
-            let service = Arc::new(Box::new(LedgerService::new()));
-            let s = service.clone();
-            let (tx, rx) = oneshot::channel::<IndyResult<()>>();
+        request["endorser"] = json!(endorser_did.to_short().0);
+        if let Some(map) = request.as_object_mut() {
+            map.remove("signature");
+        }
 
-            let future = async move {
-                let res = s.validate_action("default");
-                tx.send(res).unwrap();
-            };
+        serde_json::to_string(&request)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize request")
+    }
 
-            executor.spawn_ok(future);
+    /// Accumulate `(did, signature)` pairs into a request's `signatures`
+    /// map instead of its single-signer `signature` field, so an author and
+    /// one or more endorsers can each sign the same canonical bytes
+    /// (`get_txn_bytes_to_sign`) and have every signature submitted
+    /// together.
+    pub(crate) fn set_multi_signature(
+        &self,
+        request: &str,
+        did: &str,
+        signature: &[u8],
+    ) -> IndyResult<String> {
+        let mut request: Value = serde_json::from_str(request).map_err(|err| {
+            IndyError::from_msg(
+                IndyErrorKind::InvalidStructure,
+                format!("Request is invalid json: {:?}", err),
+            )
+        })?;
 
-            let res = rx.await;
-            debug!("-------> {:?}", res);
+        if !request.is_object() {
+            return Err(err_msg(
+                IndyErrorKind::InvalidStructure,
+                "Unable to multi-sign request as it is not an object.",
+            ));
+        }
+
+        if let Some(map) = request.as_object_mut() {
+            map.remove("signature");
+            if !map.get("signatures").map(Value::is_object).unwrap_or(false) {
+                map.insert("signatures".to_string(), json!({}));
+            }
+            map["signatures"][did] = json!(bs58::encode(signature).into_string());
+        }
+
+        serde_json::to_string(&request)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize request")
+    }
+
+    /// Raw-JSON counterpart to `begin_multi_sign`/`add_endorser_signature`
+    /// for callers passing every collected `(did, signature)` pair at once
+    /// rather than threading a typed `Request<Value>` through each hop of
+    /// an author→endorser hand-off. Applies `set_multi_signature` for each
+    /// pair in order, so later entries don't clobber earlier ones.
+    pub(crate) fn multi_sign_request(
+        &self,
+        request: &str,
+        signatures: &[(String, Vec<u8>)],
+    ) -> IndyResult<String> {
+        let mut request = request.to_string();
+
+        for (did, signature) in signatures {
+            request = self.set_multi_signature(&request, did, signature)?;
+        }
+
+        Ok(request)
+    }
+
+    /// Canonical bytes every signer of a multi-sign transaction needs to
+    /// sign over, with any signature already collected stripped out first
+    /// so an author and every subsequent endorser are guaranteed to sign
+    /// the identical bytes regardless of how far along the hand-off the
+    /// request already is.
+    pub(crate) fn multi_signed_bytes_to_sign(
+        &self,
+        txn: &Request<serde_json::Value>,
+    ) -> IndyResult<Vec<u8>> {
+        let mut value = serde_json::to_value(txn)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize request")?;
+
+        if let Some(map) = value.as_object_mut() {
+            map.remove("signature");
+            map.remove("signatures");
+        }
+
+        Ok(serialize_signature(value)?.as_bytes().to_vec())
+    }
+
+    /// Start the author→endorser multi-signature hand-off: record the
+    /// author's detached signature over `multi_signed_bytes_to_sign` into
+    /// `txn`'s `signatures` map, keyed by DID, instead of the single-signer
+    /// `signature` field a normally-submitted request would use.
+    pub(crate) fn begin_multi_sign(
+        &self,
+        txn: &mut Request<serde_json::Value>,
+        author_did: &DidValue,
+        author_sig: &[u8],
+    ) -> IndyResult<()> {
+        self.insert_multi_signature(txn, &author_did.to_short().0, author_sig)
+    }
+
+    /// Add an endorser's detached signature, over the same canonical bytes
+    /// the author signed, into `txn`'s `signatures` map.
+    pub(crate) fn add_endorser_signature(
+        &self,
+        txn: &mut Request<serde_json::Value>,
+        endorser_did: &DidValue,
+        sig: &[u8],
+    ) -> IndyResult<()> {
+        self.insert_multi_signature(txn, &endorser_did.to_short().0, sig)
+    }
+
+    /// `Request<Value>` counterpart to `set_multi_signature`, for
+    /// `begin_multi_sign`/`add_endorser_signature`'s typed callers. Rather
+    /// than reimplementing the "strip `signature`, insert into the
+    /// `signatures` map" logic against the typed request, round-trips
+    /// through `set_multi_signature`'s JSON-string form so there's one
+    /// copy of that logic.
+    fn insert_multi_signature(
+        &self,
+        txn: &mut Request<serde_json::Value>,
+        did: &str,
+        sig: &[u8],
+    ) -> IndyResult<()> {
+        let request = serde_json::to_string(&*txn)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot serialize request")?;
+
+        let signed = self.set_multi_signature(&request, did, sig)?;
+
+        *txn = serde_json::from_str(&signed)
+            .to_indy(IndyErrorKind::InvalidState, "Cannot rebuild request after multi-signing")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::domain::{
+        anoncreds::schema::AttributeNames,
+        ledger::{constants::*, node::Services, request::ProtocolVersion},
+    };
+
+    const IDENTIFIER: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+    const DEST: &str = "VsKV7grR1BUE29mG2Fm2kX";
+    const VERKEY: &str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+
+    fn identifier() -> DidValue {
+        DidValue(IDENTIFIER.to_string())
+    }
+
+    fn dest() -> DidValue {
+        DidValue(DEST.to_string())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[async_std::test]
+        async fn ledger_service_allows_send() {
+            use futures::{channel::oneshot, executor::ThreadPool};
+            use std::sync::Arc;
+
+            let executor = Arc::new(ThreadPool::new().expect("Failed to new ThreadPool"));
+            let service = Arc::new(Box::new(LedgerService::new()));
+            let s = service.clone();
+            let (tx, rx) = oneshot::channel::<IndyResult<()>>();
+
+            let future = async move {
+                let res = s.validate_action("default");
+                tx.send(res).unwrap();
+            };
+
+            executor.spawn_ok(future);
+
+            let res = rx.await;
+            debug!("-------> {:?}", res);
         }
     }
 
@@ -1248,6 +2037,46 @@ mod tests {
         check_request(&request, expected_result);
     }
 
+    #[test]
+    fn parse_get_txn_schema_response_works() {
+        let ledger_service = LedgerService::new();
+
+        let get_txn_response = json!({
+            "op": "REPLY",
+            "result": {
+                "type": GET_TXN,
+                "seqNo": 5,
+                "txn": {
+                    "data": {
+                        "name": "name",
+                        "version": "1.0",
+                        "attr_names": ["male"]
+                    },
+                    "metadata": {
+                        "from": IDENTIFIER
+                    }
+                }
+            }
+        })
+        .to_string();
+
+        let expected_id = SchemaId::new(&identifier(), "name", "1.0").unwrap();
+
+        let (id, schema_json) = ledger_service
+            .parse_get_txn_schema_response(&get_txn_response, None, None)
+            .unwrap();
+
+        assert_eq!(id, expected_id.0);
+
+        let schema: Schema = serde_json::from_str(&schema_json).unwrap();
+        let Schema::SchemaV1(schema) = schema;
+        assert_eq!(schema.id, expected_id);
+        assert_eq!(schema.name, "name");
+        assert_eq!(schema.version, "1.0");
+        assert_eq!(schema.seq_no, Some(5));
+        assert!(schema.attr_names.0.contains("male"));
+    }
+
     #[test]
     fn build_get_cred_def_request_works() {
         ProtocolVersion::set(2);
@@ -1373,6 +2202,77 @@ mod tests {
         assert_kind!(IndyErrorKind::InvalidStructure, res);
     }
 
+    mod protocol_version {
+        use super::*;
+
+        #[test]
+        fn build_get_txn_request_omits_ledger_id_under_protocol_version_1() {
+            let ledger_service = LedgerService::new();
+            ledger_service.set_protocol_version(1);
+
+            let expected_result = json!({
+                "type": GET_TXN,
+                "data": 1
+            });
+
+            let request = ledger_service
+                .build_get_txn_request(Some(&identifier()), None, 1)
+                .unwrap();
+
+            check_request(&request, expected_result);
+        }
+
+        #[test]
+        fn build_get_txn_request_includes_ledger_id_under_protocol_version_2() {
+            let ledger_service = LedgerService::new();
+            ledger_service.set_protocol_version(2);
+
+            let expected_result = json!({
+                "type": GET_TXN,
+                "data": 1,
+                "ledgerId": 1
+            });
+
+            let request = ledger_service
+                .build_get_txn_request(Some(&identifier()), None, 1)
+                .unwrap();
+
+            check_request(&request, expected_result);
+        }
+
+        #[test]
+        fn build_get_txn_request_rejects_ledger_type_selection_under_protocol_version_1() {
+            let ledger_service = LedgerService::new();
+            ledger_service.set_protocol_version(1);
+
+            let res = ledger_service.build_get_txn_request(Some(&identifier()), Some("POOL"), 1);
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+
+        #[test]
+        fn append_txn_author_agreement_acceptance_rejects_protocol_version_1() {
+            let ledger_service = LedgerService::new();
+            ledger_service.set_protocol_version(1);
+
+            let built = ledger_service
+                .build_nym_request(&identifier(), &dest(), None, None, None)
+                .unwrap();
+            let mut transaction: Request<serde_json::Value> = serde_json::from_str(&built).unwrap();
+
+            let res = ledger_service.append_txn_author_agreement_acceptance_to_request(
+                &mut transaction,
+                Some("some agreement text"),
+                Some("1.0.0"),
+                None,
+                "on_file",
+                1562367600,
+                DigestAlgorithm::default(),
+            );
+
+            assert_kind!(IndyErrorKind::InvalidState, res);
+        }
+    }
+
     #[test]
     fn validate_action_works_for_pool_restart() {
         let ledger_service = LedgerService::new();
@@ -1478,6 +2378,76 @@ mod tests {
             check_request(&request, expected_result);
         }
 
+        #[test]
+        fn build_auth_rule_request_works_for_trustee_or_steward_endorser_constraints() {
+            let ledger_service = LedgerService::new();
+
+            fn role_constraint(role: &str) -> Constraint {
+                Constraint::RoleConstraint(RoleConstraint {
+                    sig_count: 1,
+                    metadata: None,
+                    role: Some(role.to_string()),
+                    need_to_be_owner: false,
+                    off_ledger_signature: false,
+                })
+            }
+
+            // AND[ROLE(TRUSTEE), OR[ROLE(STEWARD), ROLE(ENDORSER)]]
+            let constraint = Constraint::AndConstraint(CombinationConstraint {
+                auth_constraints: vec![
+                    role_constraint(TRUSTEE),
+                    Constraint::OrConstraint(CombinationConstraint {
+                        auth_constraints: vec![role_constraint(STEWARD), role_constraint(ENDORSER)],
+                    }),
+                ],
+            });
+
+            let expected_result = json!({
+                "type": AUTH_RULE,
+                "auth_type": NYM,
+                "field": FIELD,
+                "new_value": NEW_VALUE,
+                "auth_action": AuthAction::ADD,
+                "constraint": constraint,
+            });
+
+            let request = ledger_service
+                .build_auth_rule_request(
+                    &identifier(),
+                    NYM,
+                    ADD_AUTH_ACTION,
+                    FIELD,
+                    None,
+                    Some(NEW_VALUE),
+                    constraint,
+                )
+                .unwrap();
+
+            check_request(&request, expected_result);
+
+            // Round-trip the same AND[ROLE, OR[ROLE, ROLE]] nesting through
+            // (de)serialization on its own, independent of the request
+            // envelope, so a regression in `Constraint`'s (de)serialization
+            // is caught even if it happens not to change the request JSON
+            // shape `check_request` compares above.
+            //
+            // A `FORBIDDEN` constraint variant isn't exercised here:
+            // `Constraint` is defined in the `domain::ledger::auth_rule`
+            // module, which this chunk's tree doesn't include, so there's
+            // no enum definition in this tree to add that variant to.
+            let for_round_trip = Constraint::AndConstraint(CombinationConstraint {
+                auth_constraints: vec![
+                    role_constraint(TRUSTEE),
+                    Constraint::OrConstraint(CombinationConstraint {
+                        auth_constraints: vec![role_constraint(STEWARD), role_constraint(ENDORSER)],
+                    }),
+                ],
+            });
+            let serialized = serde_json::to_value(&for_round_trip).unwrap();
+            let deserialized: Constraint = serde_json::from_value(serialized.clone()).unwrap();
+            assert_eq!(serialized, serde_json::to_value(&deserialized).unwrap());
+        }
+
         #[test]
         fn build_auth_rule_request_works_for_edit_auth_action() {
             let ledger_service = LedgerService::new();
@@ -1743,6 +2713,250 @@ mod tests {
         }
     }
 
+    mod revocation_registry {
+        use super::*;
+
+        const REVOC_REG_DEF_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:4:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag:CL_ACCUM:tag";
+        const TIMESTAMP: i64 = 1562362800;
+
+        fn revoc_reg_def_id() -> RevocationRegistryId {
+            RevocationRegistryId(REVOC_REG_DEF_ID.to_string())
+        }
+
+        #[test]
+        fn build_get_revoc_reg_def_request_works() {
+            let ledger_service = LedgerService::new();
+
+            let expected_result = json!({
+                "type": GET_REVOC_REG_DEF,
+                "id": REVOC_REG_DEF_ID,
+            });
+
+            let request = ledger_service
+                .build_get_revoc_reg_def_request(Some(&identifier()), &revoc_reg_def_id())
+                .unwrap();
+
+            check_request(&request, expected_result);
+        }
+
+        #[test]
+        fn build_get_revoc_reg_request_works() {
+            let ledger_service = LedgerService::new();
+
+            let expected_result = json!({
+                "type": GET_REVOC_REG,
+                "revocRegDefId": REVOC_REG_DEF_ID,
+                "timestamp": TIMESTAMP,
+            });
+
+            let request = ledger_service
+                .build_get_revoc_reg_request(Some(&identifier()), &revoc_reg_def_id(), TIMESTAMP)
+                .unwrap();
+
+            check_request(&request, expected_result);
+        }
+
+        #[test]
+        fn build_get_revoc_reg_delta_request_works_for_full_history() {
+            let ledger_service = LedgerService::new();
+
+            let expected_result = json!({
+                "type": GET_REVOC_REG_DELTA,
+                "revocRegDefId": REVOC_REG_DEF_ID,
+                "to": TIMESTAMP,
+            });
+
+            let request = ledger_service
+                .build_get_revoc_reg_delta_request(
+                    Some(&identifier()),
+                    &revoc_reg_def_id(),
+                    None,
+                    TIMESTAMP,
+                )
+                .unwrap();
+
+            check_request(&request, expected_result);
+        }
+
+        #[test]
+        fn build_get_revoc_reg_delta_request_works_for_partial_history() {
+            let ledger_service = LedgerService::new();
+            let from = TIMESTAMP - 3600;
+
+            let expected_result = json!({
+                "type": GET_REVOC_REG_DELTA,
+                "revocRegDefId": REVOC_REG_DEF_ID,
+                "from": from,
+                "to": TIMESTAMP,
+            });
+
+            let request = ledger_service
+                .build_get_revoc_reg_delta_request(
+                    Some(&identifier()),
+                    &revoc_reg_def_id(),
+                    Some(from),
+                    TIMESTAMP,
+                )
+                .unwrap();
+
+            check_request(&request, expected_result);
+        }
+
+        // `build_revoc_reg_def_request` and `build_revoc_reg_entry_request` take a
+        // `RevocationRegistryDefinitionV1`/`RevocationRegistryDeltaV1` whose `value`
+        // wraps ursa's CL accumulator types; those aren't constructible without real
+        // key material from the anoncreds domain module this chunk doesn't include,
+        // so only the identifier-driven GET_* requests above are covered here.
+    }
+
+    mod merge_revoc_reg_deltas {
+        use super::*;
+
+        // `accum`/`prev_accum` only ever flow through `check_delta_contiguity`
+        // as opaque `serde_json::Value`s that are compared for equality, so a
+        // plain string stands in for the real ursa accumulator value here.
+        fn delta(prev_accum: Option<&str>, accum: Option<&str>, issued: &[u32], revoked: &[u32]) -> RevocationRegistryDeltaValueJson {
+            RevocationRegistryDeltaValueJson {
+                prev_accum: prev_accum.map(|value| json!(value)),
+                accum: accum.map(|value| json!(value)),
+                issued: issued.iter().copied().collect(),
+                revoked: revoked.iter().copied().collect(),
+            }
+        }
+
+        #[test]
+        fn check_delta_contiguity_accepts_a_normal_contiguous_pair() {
+            let delta_a = delta(Some("accum0"), Some("accum1"), &[], &[]);
+            let delta_b = delta(Some("accum1"), Some("accum2"), &[], &[]);
+
+            LedgerService::check_delta_contiguity(&delta_a, &delta_b).unwrap();
+        }
+
+        #[test]
+        fn check_delta_contiguity_accepts_a_from_genesis_pair() {
+            let delta_a = delta(None, None, &[], &[]);
+            let delta_b = delta(None, Some("accum1"), &[], &[]);
+
+            LedgerService::check_delta_contiguity(&delta_a, &delta_b).unwrap();
+        }
+
+        #[test]
+        fn check_delta_contiguity_rejects_a_non_contiguous_pair() {
+            let delta_a = delta(Some("accum0"), Some("accum1"), &[], &[]);
+            let delta_b = delta(Some("some-other-accum"), Some("accum2"), &[], &[]);
+
+            let err = LedgerService::check_delta_contiguity(&delta_a, &delta_b).unwrap_err();
+            assert!(err.to_string().contains("non-contiguous"));
+        }
+
+        #[test]
+        fn check_delta_contiguity_rejects_a_from_genesis_delta_b_after_a_non_genesis_delta_a() {
+            let delta_a = delta(Some("accum0"), Some("accum1"), &[], &[]);
+            let delta_b = delta(None, Some("accum2"), &[], &[]);
+
+            let err = LedgerService::check_delta_contiguity(&delta_a, &delta_b).unwrap_err();
+            assert!(err.to_string().contains("non-contiguous"));
+        }
+
+        #[test]
+        fn merge_issued_revoked_unions_disjoint_indices() {
+            let delta_a = delta(Some("accum0"), Some("accum1"), &[1, 2], &[3]);
+            let delta_b = delta(Some("accum1"), Some("accum2"), &[4], &[5]);
+
+            let (issued, revoked) = LedgerService::merge_issued_revoked(&delta_a, &delta_b);
+            assert_eq!(issued, [1, 2, 4].iter().copied().collect());
+            assert_eq!(revoked, [3, 5].iter().copied().collect());
+        }
+
+        #[test]
+        fn merge_issued_revoked_nets_out_an_index_revoked_then_reissued() {
+            // Index 7 was revoked by delta_a and re-issued by delta_b: the two
+            // changes cancel out, so the merged delta lists it in neither set.
+            let delta_a = delta(Some("accum0"), Some("accum1"), &[], &[7]);
+            let delta_b = delta(Some("accum1"), Some("accum2"), &[7], &[]);
+
+            let (issued, revoked) = LedgerService::merge_issued_revoked(&delta_a, &delta_b);
+            assert!(issued.is_empty());
+            assert!(revoked.is_empty());
+        }
+
+        #[test]
+        fn merge_issued_revoked_nets_out_an_index_issued_then_revoked() {
+            // Index 9 was issued by delta_a and revoked by delta_b: the two
+            // changes cancel out, so the merged delta lists it in neither set.
+            let delta_a = delta(Some("accum0"), Some("accum1"), &[9], &[]);
+            let delta_b = delta(Some("accum1"), Some("accum2"), &[], &[9]);
+
+            let (issued, revoked) = LedgerService::merge_issued_revoked(&delta_a, &delta_b);
+            assert!(issued.is_empty());
+            assert!(revoked.is_empty());
+        }
+
+        // Unlike the fixtures above (which stand in plain strings for
+        // `accum`/`prev_accum` to isolate `check_delta_contiguity`/
+        // `merge_issued_revoked`), this drives `merge_revoc_reg_deltas`
+        // itself end to end against real ursa accumulator values, so the
+        // `from_parts` call in between actually type-checks and runs.
+        #[test]
+        fn merge_revoc_reg_deltas_round_trips_real_accumulators() {
+            use ursa::cl::issuer::Issuer as CryptoIssuer;
+            use ursa::cl::{CredentialSchemaBuilder, NonCredentialSchemaBuilder};
+
+            let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+            credential_schema_builder.add_attr("name").unwrap();
+            let credential_schema = credential_schema_builder.finalize().unwrap();
+
+            let mut non_credential_schema_builder = NonCredentialSchemaBuilder::new().unwrap();
+            non_credential_schema_builder
+                .add_attr("master_secret")
+                .unwrap();
+            let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+
+            let (credential_pub_key, _credential_priv_key, _correctness_proof) =
+                CryptoIssuer::new_credential_def(&non_credential_schema, &credential_schema, true)
+                    .unwrap();
+
+            let (_rev_key_pub, _rev_key_priv, reg_genesis, _tails_gen) =
+                CryptoIssuer::new_revocation_registry_def(&credential_pub_key, 5, false).unwrap();
+            let (_rev_key_pub2, _rev_key_priv2, reg_a, _tails_gen2) =
+                CryptoIssuer::new_revocation_registry_def(&credential_pub_key, 5, false).unwrap();
+
+            let issued_a: HashSet<u32> = [1].iter().copied().collect();
+            let delta_a_value =
+                CryproRevocationRegistryDelta::from_parts(None, &reg_genesis, &issued_a, &HashSet::new());
+            let delta_a_json = serde_json::to_string(&RevocationRegistryDelta::RevocationRegistryDeltaV1(
+                RevocationRegistryDeltaV1 { value: delta_a_value },
+            ))
+            .unwrap();
+
+            let revoked_b: HashSet<u32> = [2].iter().copied().collect();
+            let delta_b_value = CryproRevocationRegistryDelta::from_parts(
+                Some(&reg_genesis),
+                &reg_a,
+                &HashSet::new(),
+                &revoked_b,
+            );
+            let delta_b_json = serde_json::to_string(&RevocationRegistryDelta::RevocationRegistryDeltaV1(
+                RevocationRegistryDeltaV1 { value: delta_b_value },
+            ))
+            .unwrap();
+
+            let ledger_service = LedgerService::new();
+            let merged_json = ledger_service
+                .merge_revoc_reg_deltas(&delta_a_json, &delta_b_json)
+                .unwrap();
+
+            let merged = LedgerService::parse_delta_value(&merged_json).unwrap();
+            assert!(merged.prev_accum.is_none());
+            assert_eq!(
+                merged.accum,
+                Some(serde_json::to_value(&reg_a).unwrap()["accum"].clone()),
+            );
+            assert_eq!(merged.issued, issued_a);
+            assert_eq!(merged.revoked, revoked_b);
+        }
+    }
+
     mod acceptance_mechanism {
         use super::*;
 
@@ -1872,4 +3086,250 @@ mod tests {
         let request: serde_json::Value = serde_json::from_str(request).unwrap();
         assert_eq!(request["operation"], expected_result);
     }
+
+    mod txn_author_agreement_acceptance {
+        use super::*;
+
+        const TEXT: &str = "some agreement text";
+        const VERSION: &str = "1.0.0";
+        const MECHANISM: &str = "on_file";
+        const TIME: u64 = 1562367600;
+
+        fn built_transaction() -> Request<serde_json::Value> {
+            let ledger_service = LedgerService::new();
+            let built = ledger_service
+                .build_nym_request(&identifier(), &dest(), None, None, None)
+                .unwrap();
+            serde_json::from_str(&built).unwrap()
+        }
+
+        #[test]
+        fn append_txn_author_agreement_acceptance_to_request_works_for_text_and_version() {
+            let ledger_service = LedgerService::new();
+            let mut transaction = built_transaction();
+
+            ledger_service
+                .append_txn_author_agreement_acceptance_to_request(
+                    &mut transaction,
+                    Some(TEXT),
+                    Some(VERSION),
+                    None,
+                    MECHANISM,
+                    TIME,
+                    DigestAlgorithm::default(),
+                )
+                .unwrap();
+
+            let expected_digest = hex::encode(
+                openssl_hash((VERSION.to_string() + TEXT).as_bytes()).unwrap(),
+            );
+
+            let taa_acceptance = transaction.taa_acceptance.as_ref().unwrap();
+            assert_eq!(taa_acceptance.mechanism, MECHANISM);
+            assert_eq!(taa_acceptance.taa_digest, expected_digest);
+            assert_eq!(
+                taa_acceptance.time,
+                LedgerService::datetime_to_date_timestamp(TIME)
+            );
+        }
+
+        #[test]
+        fn append_txn_author_agreement_acceptance_to_request_works_for_digest() {
+            let ledger_service = LedgerService::new();
+            let mut transaction = built_transaction();
+
+            let digest = hex::encode(openssl_hash((VERSION.to_string() + TEXT).as_bytes()).unwrap());
+
+            ledger_service
+                .append_txn_author_agreement_acceptance_to_request(
+                    &mut transaction,
+                    None,
+                    None,
+                    Some(&digest),
+                    MECHANISM,
+                    TIME,
+                    DigestAlgorithm::default(),
+                )
+                .unwrap();
+
+            let taa_acceptance = transaction.taa_acceptance.as_ref().unwrap();
+            assert_eq!(taa_acceptance.taa_digest, digest);
+            assert_eq!(
+                taa_acceptance.time,
+                LedgerService::datetime_to_date_timestamp(TIME)
+            );
+        }
+
+        #[test]
+        fn append_txn_author_agreement_acceptance_to_request_fails_for_neither_text_nor_digest() {
+            let ledger_service = LedgerService::new();
+            let mut transaction = built_transaction();
+
+            let res = ledger_service.append_txn_author_agreement_acceptance_to_request(
+                &mut transaction,
+                None,
+                None,
+                None,
+                MECHANISM,
+                TIME,
+                DigestAlgorithm::default(),
+            );
+
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+
+        #[test]
+        fn append_txn_author_agreement_acceptance_to_request_fails_for_mismatched_digest() {
+            let ledger_service = LedgerService::new();
+            let mut transaction = built_transaction();
+
+            let wrong_digest = hex::encode(openssl_hash(b"not the real agreement").unwrap());
+
+            let res = ledger_service.append_txn_author_agreement_acceptance_to_request(
+                &mut transaction,
+                Some(TEXT),
+                Some(VERSION),
+                Some(&wrong_digest),
+                MECHANISM,
+                TIME,
+                DigestAlgorithm::default(),
+            );
+
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+
+        #[test]
+        fn append_txn_author_agreement_acceptance_to_request_fails_for_text_without_version() {
+            let ledger_service = LedgerService::new();
+            let mut transaction = built_transaction();
+
+            let res = ledger_service.append_txn_author_agreement_acceptance_to_request(
+                &mut transaction,
+                Some(TEXT),
+                None,
+                None,
+                MECHANISM,
+                TIME,
+                DigestAlgorithm::default(),
+            );
+
+            assert_kind!(IndyErrorKind::InvalidStructure, res);
+        }
+    }
+
+    mod endorser_delegation {
+        use super::*;
+
+        const ENDORSER_DID: &str = "2hoqvcwupRTUNkXn6ArYzs";
+        const AUTHOR_DID: &str = "V4SGRU86Z58d6TV7PBUe6f";
+
+        fn endorser_did() -> DidValue {
+            DidValue(ENDORSER_DID.to_string())
+        }
+
+        fn built_request() -> String {
+            let ledger_service = LedgerService::new();
+            ledger_service
+                .build_nym_request(&identifier(), &dest(), None, None, None)
+                .unwrap()
+        }
+
+        #[test]
+        fn append_request_endorser_leaves_operation_unchanged_but_adds_endorser() {
+            let ledger_service = LedgerService::new();
+            let request = built_request();
+
+            let before: Value = serde_json::from_str(&request).unwrap();
+            let endorsed = ledger_service
+                .append_request_endorser(&request, &endorser_did())
+                .unwrap();
+            let after: Value = serde_json::from_str(&endorsed).unwrap();
+
+            assert_eq!(before["operation"], after["operation"]);
+            assert_eq!(after["endorser"], json!(ENDORSER_DID));
+            assert!(after.get("signature").is_none());
+        }
+
+        #[test]
+        fn multi_sign_request_assembles_signatures_keyed_by_did() {
+            let ledger_service = LedgerService::new();
+            let request = built_request();
+
+            let signed = ledger_service
+                .multi_sign_request(
+                    &request,
+                    &[
+                        (AUTHOR_DID.to_string(), b"author-signature".to_vec()),
+                        (ENDORSER_DID.to_string(), b"endorser-signature".to_vec()),
+                    ],
+                )
+                .unwrap();
+
+            let value: Value = serde_json::from_str(&signed).unwrap();
+            assert!(value.get("signature").is_none());
+            assert_eq!(
+                value["signatures"][AUTHOR_DID],
+                json!(bs58::encode(b"author-signature").into_string())
+            );
+            assert_eq!(
+                value["signatures"][ENDORSER_DID],
+                json!(bs58::encode(b"endorser-signature").into_string())
+            );
+        }
+    }
+
+    mod multi_sign {
+        use super::*;
+
+        const AUTHOR_DID: &str = "V4SGRU86Z58d6TV7PBUe6f";
+        const ENDORSER_DID: &str = "2hoqvcwupRTUNkXn6ArYzs";
+
+        fn author() -> DidValue {
+            DidValue(AUTHOR_DID.to_string())
+        }
+
+        fn endorser() -> DidValue {
+            DidValue(ENDORSER_DID.to_string())
+        }
+
+        fn unsigned_txn() -> Request<serde_json::Value> {
+            let ledger_service = LedgerService::new();
+            let built = ledger_service
+                .build_nym_request(&identifier(), &dest(), None, None, None)
+                .unwrap();
+            serde_json::from_str(&built).unwrap()
+        }
+
+        #[test]
+        fn author_and_endorser_sign_the_same_bytes() {
+            let ledger_service = LedgerService::new();
+            let mut txn = unsigned_txn();
+
+            let author_bytes = ledger_service.multi_signed_bytes_to_sign(&txn).unwrap();
+            ledger_service
+                .begin_multi_sign(&mut txn, &author(), b"author-signature")
+                .unwrap();
+
+            // Adding the author's signature must not change what an
+            // endorser needs to sign: both parties sign the identical
+            // canonicalization of the request body.
+            let endorser_bytes = ledger_service.multi_signed_bytes_to_sign(&txn).unwrap();
+            assert_eq!(author_bytes, endorser_bytes);
+
+            ledger_service
+                .add_endorser_signature(&mut txn, &endorser(), b"endorser-signature")
+                .unwrap();
+
+            let value = serde_json::to_value(&txn).unwrap();
+            assert!(value.get("signature").is_none());
+            assert_eq!(
+                value["signatures"][AUTHOR_DID],
+                json!(bs58::encode(b"author-signature").into_string())
+            );
+            assert_eq!(
+                value["signatures"][ENDORSER_DID],
+                json!(bs58::encode(b"endorser-signature").into_string())
+            );
+        }
+    }
 }
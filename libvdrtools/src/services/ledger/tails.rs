@@ -0,0 +1,262 @@
+//! Resolves, verifies, and disk-caches the tails file a
+//! `RevocationRegistryDefinition` references by hash/location. Nothing else
+//! in the crate manages these files; callers that build or verify a
+//! revocation proof need the actual tails bytes, not just the definition.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use indy_api_types::errors::prelude::*;
+use indy_utils::crypto::hash::hash as openssl_hash;
+
+use crate::domain::anoncreds::credential_definition::CredentialDefinitionId;
+use crate::domain::anoncreds::revocation_registry_definition::{
+    RevocationRegistryDefinitionV1, RevocationRegistryId,
+};
+use crate::domain::crypto::did::DidValue;
+
+/// `purge`'s filtering semantics mirror what a tails server needs to offer:
+/// drop everything, everything for one registry, everything under one cred
+/// def, or everything authored by one issuer DID.
+pub enum PurgeFilter {
+    All,
+    RevocationRegistry(RevocationRegistryId),
+    CredentialDefinition(CredentialDefinitionId),
+    Issuer(DidValue),
+}
+
+/// Disk cache for tails files, addressable by rev reg id / cred def id /
+/// issuer DID so `purge` can target any of those scopes without having to
+/// know the full directory layout up front.
+pub struct TailsCache {
+    base_dir: PathBuf,
+}
+
+impl TailsCache {
+    pub fn new(base_dir: PathBuf) -> Self {
+        TailsCache { base_dir }
+    }
+
+    /// Resolve the tails file for `rev_reg_def`, downloading it via
+    /// `fetch` if it isn't already cached, and verify its contents against
+    /// `tailsHash` before returning the path. A corrupt or truncated
+    /// download is deleted rather than handed back to the caller.
+    pub async fn resolve<F, Fut>(
+        &self,
+        rev_reg_def: &RevocationRegistryDefinitionV1,
+        issuer_did: &DidValue,
+        fetch: F,
+    ) -> IndyResult<PathBuf>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: std::future::Future<Output = IndyResult<Vec<u8>>>,
+    {
+        let path = self.path_for(rev_reg_def, issuer_did)?;
+
+        if path.exists() {
+            if self.verify(&path, &rev_reg_def.value.tails_hash).is_ok() {
+                return Ok(path);
+            }
+            // Stale/corrupt cache entry: remove it and re-download below.
+            let _ = fs::remove_file(&path);
+        }
+
+        let bytes = fetch(rev_reg_def.value.tails_location.clone()).await?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).to_indy(
+                IndyErrorKind::IOError,
+                "Cannot create tails cache directory",
+            )?;
+        }
+        fs::write(&path, &bytes).to_indy(IndyErrorKind::IOError, "Cannot write tails file")?;
+
+        self.verify(&path, &rev_reg_def.value.tails_hash).map_err(|err| {
+            let _ = fs::remove_file(&path);
+            err
+        })?;
+
+        Ok(path)
+    }
+
+    /// Recompute the downloaded file's hash (SHA-256 via `openssl_hash`,
+    /// base58-encoded) and confirm it matches the definition's `tailsHash`.
+    pub fn verify(&self, path: &Path, expected_tails_hash: &str) -> IndyResult<()> {
+        let bytes = fs::read(path).to_indy(IndyErrorKind::IOError, "Cannot read tails file")?;
+        let digest = openssl_hash(&bytes)?;
+        let actual = bs58::encode(digest).into_string();
+
+        if actual != expected_tails_hash {
+            return Err(IndyError::from_msg(
+                IndyErrorKind::InvalidState,
+                format!(
+                    "Tails file hash mismatch: expected {}, got {}",
+                    expected_tails_hash, actual
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Remove cached tails files matching `filter`.
+    pub fn purge(&self, filter: PurgeFilter) -> IndyResult<()> {
+        match filter {
+            PurgeFilter::All => {
+                if self.base_dir.exists() {
+                    fs::remove_dir_all(&self.base_dir)
+                        .to_indy(IndyErrorKind::IOError, "Cannot purge tails cache")?;
+                }
+                Ok(())
+            }
+            PurgeFilter::Issuer(did) => {
+                let dir = self.join_component(&self.base_dir, &did.to_unqualified().0)?;
+                Self::remove_dir_if_exists(&dir)
+            }
+            PurgeFilter::CredentialDefinition(cred_def_id) => {
+                // Same two components `path_for` joins before appending the
+                // rev reg id: this is the directory holding every cached
+                // tails file for this cred def, so a directory removal is
+                // correct here (unlike the single-registry case below).
+                let cred_def_id = cred_def_id.to_unqualified();
+                let (issuer_did, ..) = cred_def_id.parts().ok_or(IndyError::from_msg(
+                    IndyErrorKind::InvalidStructure,
+                    format!("Credential Definition ID `{}` is malformed", cred_def_id.0),
+                ))?;
+                let dir = self.join_component(&self.base_dir, &issuer_did.0)?;
+                let dir = self.join_component(&dir, &cred_def_id.0)?;
+                Self::remove_dir_if_exists(&dir)
+            }
+            PurgeFilter::RevocationRegistry(rev_reg_id) => {
+                // `path_for` writes the tails file itself at
+                // `base/issuer/cred_def_id/rev_reg_id`, so unlike the other
+                // filters this one names a file, not a directory.
+                let rev_reg_id = rev_reg_id.to_unqualified();
+                let (issuer_did, cred_def_id, _tag) =
+                    rev_reg_id.parts().ok_or(IndyError::from_msg(
+                        IndyErrorKind::InvalidStructure,
+                        format!("Revocation Registry ID `{}` is malformed", rev_reg_id.0),
+                    ))?;
+                let dir = self.join_component(&self.base_dir, &issuer_did.0)?;
+                let dir = self.join_component(&dir, &cred_def_id.0)?;
+                let path = self.join_component(&dir, &rev_reg_id.0)?;
+                Self::remove_file_if_exists(&path)
+            }
+        }
+    }
+
+    fn remove_dir_if_exists(dir: &Path) -> IndyResult<()> {
+        if dir.exists() {
+            fs::remove_dir_all(dir).to_indy(IndyErrorKind::IOError, "Cannot purge tails cache entry")?;
+        }
+        Ok(())
+    }
+
+    fn remove_file_if_exists(path: &Path) -> IndyResult<()> {
+        if path.exists() {
+            fs::remove_file(path).to_indy(IndyErrorKind::IOError, "Cannot purge tails cache entry")?;
+        }
+        Ok(())
+    }
+
+    fn path_for(
+        &self,
+        rev_reg_def: &RevocationRegistryDefinitionV1,
+        issuer_did: &DidValue,
+    ) -> IndyResult<PathBuf> {
+        let rev_reg_id = rev_reg_def.id.to_unqualified();
+        let path = self.join_component(&self.base_dir, &issuer_did.to_unqualified().0)?;
+        let path = self.join_component(&path, &rev_reg_def.cred_def_id.to_unqualified().0)?;
+        self.join_component(&path, &rev_reg_id.0)
+    }
+
+    /// Join `component` (an issuer/cred-def/rev-reg id or tag, ultimately
+    /// sourced from ledger data an issuer controls) onto `base`, rejecting
+    /// anything that isn't a single plain path segment. Without this, a
+    /// component containing `..` or a path separator could walk the
+    /// resulting path outside `base_dir` entirely.
+    fn join_component(&self, base: &Path, component: &str) -> IndyResult<PathBuf> {
+        let is_single_plain_segment = !component.is_empty()
+            && component != "."
+            && component != ".."
+            && !component.contains('/')
+            && !component.contains('\\');
+
+        if !is_single_plain_segment {
+            return Err(IndyError::from_msg(
+                IndyErrorKind::InvalidStructure,
+                format!("`{}` is not a valid tails cache path component", component),
+            ));
+        }
+
+        Ok(base.join(component))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issuer_did() -> DidValue {
+        DidValue("NcYxiDXkpYi6ov5FcYDi1e".to_string())
+    }
+
+    fn cred_def_id() -> CredentialDefinitionId {
+        CredentialDefinitionId("NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag".to_string())
+    }
+
+    fn rev_reg_id() -> RevocationRegistryId {
+        RevocationRegistryId(
+            "NcYxiDXkpYi6ov5FcYDi1e:4:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag:CL_ACCUM:tag2".to_string(),
+        )
+    }
+
+    /// Lays out a cached tails file exactly where `path_for` would put it
+    /// (`base/issuer/cred_def_id/rev_reg_id`), without going through
+    /// `resolve`, and returns the cache plus the cred-def directory and the
+    /// rev-reg file inside it.
+    fn cache_with_cached_entry(test_tag: &str) -> (TailsCache, PathBuf, PathBuf) {
+        let base_dir = std::env::temp_dir().join(format!("indy_tails_cache_purge_test_{}", test_tag));
+        let _ = fs::remove_dir_all(&base_dir);
+
+        let dir = base_dir.join(&issuer_did().0).join(&cred_def_id().0);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(&rev_reg_id().0);
+        fs::write(&file, b"tails bytes").unwrap();
+
+        (TailsCache::new(base_dir), dir, file)
+    }
+
+    #[test]
+    fn purge_credential_definition_removes_the_directory_path_for_would_populate() {
+        let (cache, dir, file) = cache_with_cached_entry("cred_def");
+        assert!(file.exists());
+
+        cache
+            .purge(PurgeFilter::CredentialDefinition(cred_def_id()))
+            .unwrap();
+
+        assert!(!dir.exists());
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn purge_revocation_registry_removes_only_the_matching_cached_file() {
+        let (cache, dir, file) = cache_with_cached_entry("rev_reg");
+
+        // A sibling tails file under the same cred def, for a different
+        // registry, which a purge scoped to `rev_reg_id()` must leave alone.
+        let sibling = dir.join("some-other-rev-reg-id");
+        fs::write(&sibling, b"tails bytes").unwrap();
+
+        cache
+            .purge(PurgeFilter::RevocationRegistry(rev_reg_id()))
+            .unwrap();
+
+        assert!(!file.exists());
+        assert!(sibling.exists());
+        assert!(dir.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
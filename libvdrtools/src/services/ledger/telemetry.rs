@@ -0,0 +1,121 @@
+//! Optional OpenTelemetry instrumentation for `LedgerService`, gated behind
+//! the `otel_metrics` feature so the dependency stays opt-in. `build_*`
+//! calls that go through the `build_result!` macro and `parse_get_*` calls
+//! that go through `LedgerService::parse_response_instrumented` are the two
+//! chokepoints where we tag a span with the txn type and record
+//! counters/duration, instead of annotating every call site individually.
+//! Calls that bypass both (e.g. `build_get_validator_info_request`,
+//! `parse_get_auth_rule_response`) are not instrumented.
+//!
+//! Enable with the `otel_metrics` feature and call `init_otel` once at
+//! startup with a configured OTLP endpoint. `init_otel` installs an OTLP
+//! trace exporter only: the counters and histogram recorded here go to
+//! whatever global meter provider is installed, and log output stays on
+//! `#[logfn(Info)]`.
+
+use std::time::Instant;
+
+use opentelemetry::global;
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::trace::{Span, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+const METER_NAME: &str = "libvdrtools.ledger";
+
+#[derive(Clone)]
+pub struct LedgerMetrics {
+    meter: Meter,
+    built: Counter<u64>,
+    parse_ok: Counter<u64>,
+    parse_err: Counter<u64>,
+    duration: Histogram<f64>,
+}
+
+impl LedgerMetrics {
+    pub fn new() -> Self {
+        let meter = global::meter(METER_NAME);
+        let built = meter.u64_counter("ledger.requests_built").init();
+        let parse_ok = meter.u64_counter("ledger.parse_successes").init();
+        let parse_err = meter.u64_counter("ledger.parse_failures").init();
+        let duration = meter.f64_histogram("ledger.call_duration_seconds").init();
+
+        LedgerMetrics {
+            meter,
+            built,
+            parse_ok,
+            parse_err,
+            duration,
+        }
+    }
+}
+
+/// Configure the global OpenTelemetry trace pipeline to export spans to
+/// `otlp_endpoint`. Metrics and logs are not exported by it.
+/// Call once at process startup before any `LedgerService` call.
+pub fn init_otel(otlp_endpoint: &str) -> Result<(), opentelemetry::trace::TraceError> {
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(otlp_endpoint))
+        .install_batch(opentelemetry::runtime::Tokio)?;
+
+    global::set_tracer_provider(tracer.provider().ok_or(opentelemetry::trace::TraceError::Other(
+        "Failed to install OTLP tracer provider".into(),
+    ))?);
+
+    Ok(())
+}
+
+/// RAII span+timer for one `build_*`/`parse_*` call, tagged with the txn
+/// type name (see `txn_name_to_code`/`constants`). Must be started *before*
+/// the call it measures runs (see `LedgerService::instrument_build`/
+/// `instrument_parse`), since `start` is when the timer begins. Dropping it
+/// records the duration histogram automatically, using the elapsed time
+/// since `start`.
+pub struct TxnSpan {
+    span: global::BoxedSpan,
+    start: Instant,
+    txn_type: String,
+    metrics: LedgerMetrics,
+}
+
+impl TxnSpan {
+    pub fn start(operation: &str, txn_type: &str, metrics: &LedgerMetrics) -> Self {
+        let tracer = global::tracer(METER_NAME);
+        let mut span = tracer.start(operation.to_string());
+        span.set_attribute(KeyValue::new("txn_type", txn_type.to_string()));
+
+        TxnSpan {
+            span,
+            start: Instant::now(),
+            txn_type: txn_type.to_string(),
+            metrics: metrics.clone(),
+        }
+    }
+
+    pub fn record_built(&mut self) {
+        self.metrics
+            .built
+            .add(&Context::current(), 1, &[KeyValue::new("txn_type", self.txn_type.clone())]);
+    }
+
+    pub fn record_parse_result(&mut self, ok: bool) {
+        let labels = &[KeyValue::new("txn_type", self.txn_type.clone())];
+        if ok {
+            self.metrics.parse_ok.add(&Context::current(), 1, labels);
+        } else {
+            self.metrics.parse_err.add(&Context::current(), 1, labels);
+        }
+    }
+}
+
+impl Drop for TxnSpan {
+    fn drop(&mut self) {
+        let elapsed_seconds = self.start.elapsed().as_secs_f64();
+        self.metrics.duration.record(
+            &Context::current(),
+            elapsed_seconds,
+            &[KeyValue::new("txn_type", self.txn_type.clone())],
+        );
+        self.span.end();
+    }
+}
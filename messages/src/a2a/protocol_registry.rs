@@ -8,11 +8,19 @@ use crate::protocols::discovery::disclose::ProtocolDescriptor;
 
 pub struct ProtocolRegistry {
     protocols: Vec<ProtocolDescriptor>,
+    /// `goal-code`/`governance-framework` (and any future non-`protocol`)
+    /// features this agent discloses. Kept separate from `protocols`
+    /// because `ProtocolDescriptor` is a `pid`/`roles` pair with no room
+    /// for a `feature_type`; see `register_feature`.
+    features: Vec<RegisteredFeature>,
 }
 
 impl ProtocolRegistry {
     pub fn init() -> ProtocolRegistry {
-        let mut registry = ProtocolRegistry { protocols: Vec::new() };
+        let mut registry = ProtocolRegistry {
+            protocols: Vec::new(),
+            features: Vec::new(),
+        };
         let actors = actors::get_actors();
 
         for family in MessageFamilies::iter() {
@@ -60,27 +68,174 @@ impl ProtocolRegistry {
         }
     }
 
+    /// Register a non-`protocol` feature (a `goal-code` or
+    /// `governance-framework` entry, per RFC 0557's feature-type
+    /// vocabulary) this agent discloses, so `get_features_for_queries` can
+    /// match it the same way it matches `protocol` entries.
+    pub fn register_feature(&mut self, feature_type: &str, id: &str) {
+        self.features.push(RegisteredFeature {
+            feature_type: feature_type.to_string(),
+            id: id.to_string(),
+        });
+    }
+
     pub fn get_protocols_for_query(&self, query: Option<&str>) -> Vec<ProtocolDescriptor> {
         match query {
             Some(query_) if query_ == "*" => self.protocols.clone(),
-            Some(query_) => match Regex::new(query_) {
-                Ok(re) => self
-                    .protocols
+            Some(query_) => {
+                let re = Self::wildcard_query_regex(query_);
+                self.protocols
                     .iter()
                     .filter(|protocol| re.is_match(&protocol.pid))
                     .cloned()
-                    .collect(),
-                Err(_) => vec![],
-            },
+                    .collect()
+            }
             None => self.protocols.clone(),
         }
     }
 
+    /// Compile an Aries Discover Features `match` pattern into a regex,
+    /// per the spec's query semantics: `*` is the only wildcard (matching
+    /// any suffix/infix), everything else is a literal, and the whole
+    /// pattern is anchored so a query can't match a mere substring of a
+    /// pid. Built by escaping every regex metacharacter in `pattern` and
+    /// only then re-introducing `.*` for the escaped `*`s, so an
+    /// attacker-supplied pattern can never smuggle in arbitrary regex
+    /// syntax (the ReDoS/footgun `Regex::new(query_)` used to allow).
+    fn wildcard_query_regex(pattern: &str) -> Regex {
+        let escaped = regex::escape(pattern).replace("\\*", ".*");
+        let anchored = format!("^{}$", escaped);
+        Regex::new(&anchored).unwrap_or_else(|_| Regex::new("a^").expect("never matches, always valid"))
+    }
+
+    /// Evaluate a Discover Features 2.0 `queries` array (Aries RFC 0557)
+    /// against the registry and return the combined, deduplicated
+    /// disclosure, drawn from both the versioned `protocol` entries and
+    /// whatever `goal-code`/`governance-framework` entries were registered
+    /// via `register_feature`.
+    pub fn get_features_for_queries(&self, queries: &[FeatureQuery]) -> Vec<Disclosure> {
+        let mut disclosed: Vec<Disclosure> = Vec::new();
+
+        for query in queries {
+            if query.feature_type == "protocol" {
+                for protocol in self.get_protocols_for_query(Some(&query.match_)) {
+                    let disclosure = Disclosure {
+                        feature_type: "protocol".to_string(),
+                        id: protocol.pid,
+                        roles: protocol.roles,
+                    };
+                    if !disclosed.contains(&disclosure) {
+                        disclosed.push(disclosure);
+                    }
+                }
+                continue;
+            }
+
+            let re = Self::wildcard_query_regex(&query.match_);
+            for feature in self
+                .features
+                .iter()
+                .filter(|feature| feature.feature_type == query.feature_type)
+                .filter(|feature| re.is_match(&feature.id))
+            {
+                let disclosure = Disclosure {
+                    feature_type: feature.feature_type.clone(),
+                    id: feature.id.clone(),
+                    roles: None,
+                };
+                if !disclosed.contains(&disclosure) {
+                    disclosed.push(disclosure);
+                }
+            }
+        }
+
+        disclosed
+    }
+
+    /// Parse a pid like `https://didcomm.org/connections/1.0` into its
+    /// `(family_uri, major, minor)` parts, where `family_uri` is
+    /// everything before the trailing `major.minor` segment.
+    fn parse_pid_version(pid: &str) -> Option<(&str, u32, u32)> {
+        let (family, version) = pid.rsplit_once('/')?;
+        let (major, minor) = version.split_once('.')?;
+        Some((family, major.parse().ok()?, minor.parse().ok()?))
+    }
+
+    /// Every `(major, minor)` pair this registry supports for `family`
+    /// (e.g. `https://didcomm.org/connections`), so a caller can
+    /// advertise the version range it's willing to negotiate.
+    pub fn supported_versions(&self, family: &str) -> Vec<(u32, u32)> {
+        self.protocols
+            .iter()
+            .filter_map(|protocol| Self::parse_pid_version(&protocol.pid))
+            .filter(|(protocol_family, _, _)| *protocol_family == family)
+            .map(|(_, major, minor)| (major, minor))
+            .collect()
+    }
+
+    /// Intersect our supported versions for `family` with a peer's
+    /// disclosed `peer_versions`, and return the descriptor for the
+    /// highest mutually-supported `(major, minor)`: major must match
+    /// exactly, minor picks the max common value. This is what lets two
+    /// agents agree on one protocol version instead of one side just
+    /// failing or guessing. Returns `None` if no version of `family` is
+    /// supported by both sides.
+    pub fn get_best_version_for_family(
+        &self,
+        family: &str,
+        peer_versions: &[ProtocolDescriptor],
+    ) -> Option<ProtocolDescriptor> {
+        let our_versions = self.supported_versions(family);
+
+        peer_versions
+            .iter()
+            .filter_map(|descriptor| {
+                let (peer_family, major, minor) = Self::parse_pid_version(&descriptor.pid)?;
+                if peer_family == family && our_versions.contains(&(major, minor)) {
+                    Some((major, minor, descriptor))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(major, minor, _)| (*major, *minor))
+            .map(|(_, _, descriptor)| descriptor.clone())
+    }
+
     pub fn protocols(&self) -> Vec<ProtocolDescriptor> {
         self.protocols.clone()
     }
 }
 
+/// A single match criterion from a Discover Features 2.0 `queries`
+/// message (Aries RFC 0557): a `feature-type` (`protocol`, `goal-code`,
+/// or `governance-framework`) paired with a `*`-wildcard `match` pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeatureQuery {
+    pub feature_type: String,
+    pub match_: String,
+}
+
+/// A non-`protocol` feature registered via `ProtocolRegistry::register_feature`
+/// (a `goal-code` or `governance-framework` entry). `ProtocolDescriptor` is
+/// a `pid`/`roles` pair owned by the `discovery::disclose` module and isn't
+/// a fit for the wider RFC 0557 feature-type vocabulary, so these are kept
+/// in the registry's own list instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RegisteredFeature {
+    feature_type: String,
+    id: String,
+}
+
+/// One entry of a Discover Features 2.0 disclosure: the `feature-type` and
+/// `id` of a matched protocol, goal-code, or governance-framework, plus the
+/// protocol-only `roles` this agent plays in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Disclosure {
+    pub feature_type: String,
+    pub id: String,
+    pub roles: Option<Vec<Actors>>,
+}
+
 #[cfg(test)]
 #[cfg(feature = "general_test")]
 pub mod unit_tests {
@@ -106,6 +261,7 @@ pub mod unit_tests {
     fn _protocol_registry() -> ProtocolRegistry {
         ProtocolRegistry {
             protocols: _protocols(),
+            features: Vec::new(),
         }
     }
 
@@ -178,7 +334,7 @@ pub mod unit_tests {
         let protocols = registry.get_protocols_for_query(None);
         assert!(!protocols.is_empty());
 
-        let protocols = registry.get_protocols_for_query(Some("https://didcomm.org/connections"));
+        let protocols = registry.get_protocols_for_query(Some("https://didcomm.org/connections*"));
         let expected_protocols = vec![ProtocolDescriptor {
             pid: MessageFamilies::Connections.id(),
             roles: None,
@@ -192,4 +348,181 @@ pub mod unit_tests {
         }];
         assert_eq!(expected_protocols, protocols);
     }
+
+    #[test]
+    fn test_get_protocols_for_query_anchors_the_whole_pid_not_a_substring() {
+        let registry: ProtocolRegistry = _protocol_registry();
+
+        // A bare query with no `*` wildcard used to match via unanchored
+        // regex substring search; it must now require a full match.
+        let protocols = registry.get_protocols_for_query(Some("protocol_1.0"));
+        assert!(protocols.is_empty());
+    }
+
+    #[test]
+    fn test_get_protocols_for_query_treats_query_regex_metacharacters_as_literals() {
+        let registry: ProtocolRegistry = _protocol_registry();
+
+        // "protocol_1.0_test" has `_` where this query has `.`: as a regex
+        // metacharacter the query's `.` would match it, as a literal it
+        // must not.
+        let protocols = registry.get_protocols_for_query(Some("protocol.1.0_test"));
+        assert!(protocols.is_empty());
+
+        let protocols = registry.get_protocols_for_query(Some("0_test.0_test"));
+        assert_eq!(
+            vec![ProtocolDescriptor {
+                pid: "0_test.0_test".to_string(),
+                roles: None,
+            }],
+            protocols
+        );
+    }
+
+    #[test]
+    fn test_get_features_for_queries_combines_multiple_protocol_criteria() {
+        let registry: ProtocolRegistry = _protocol_registry();
+
+        let queries = vec![
+            FeatureQuery {
+                feature_type: "protocol".to_string(),
+                match_: "protocol_1.0*".to_string(),
+            },
+            FeatureQuery {
+                feature_type: "protocol".to_string(),
+                match_: "0_test.0_test".to_string(),
+            },
+        ];
+
+        let disclosed = registry.get_features_for_queries(&queries);
+        assert_eq!(3, disclosed.len());
+        assert!(disclosed.iter().all(|d| d.feature_type == "protocol"));
+    }
+
+    #[test]
+    fn test_get_features_for_queries_ignores_unregistered_feature_types() {
+        let registry: ProtocolRegistry = _protocol_registry();
+
+        let queries = vec![FeatureQuery {
+            feature_type: "goal-code".to_string(),
+            match_: "*".to_string(),
+        }];
+
+        assert!(registry.get_features_for_queries(&queries).is_empty());
+    }
+
+    #[test]
+    fn test_get_features_for_queries_returns_registered_goal_codes_and_governance_frameworks() {
+        let mut registry: ProtocolRegistry = _protocol_registry();
+        registry.register_feature("goal-code", "aries.vc.issue");
+        registry.register_feature("governance-framework", "https://governance.example/v1");
+
+        let queries = vec![
+            FeatureQuery {
+                feature_type: "goal-code".to_string(),
+                match_: "aries.vc.*".to_string(),
+            },
+            FeatureQuery {
+                feature_type: "governance-framework".to_string(),
+                match_: "*".to_string(),
+            },
+            FeatureQuery {
+                feature_type: "goal-code".to_string(),
+                match_: "no.such.code".to_string(),
+            },
+        ];
+
+        let mut disclosed = registry.get_features_for_queries(&queries);
+        disclosed.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(
+            vec![
+                Disclosure {
+                    feature_type: "goal-code".to_string(),
+                    id: "aries.vc.issue".to_string(),
+                    roles: None,
+                },
+                Disclosure {
+                    feature_type: "governance-framework".to_string(),
+                    id: "https://governance.example/v1".to_string(),
+                    roles: None,
+                },
+            ],
+            disclosed
+        );
+    }
+
+    fn _versioned_protocol_registry() -> ProtocolRegistry {
+        ProtocolRegistry {
+            protocols: vec![
+                ProtocolDescriptor {
+                    pid: "https://didcomm.org/connections/1.0".to_string(),
+                    roles: None,
+                },
+                ProtocolDescriptor {
+                    pid: "https://didcomm.org/connections/1.1".to_string(),
+                    roles: None,
+                },
+            ],
+            features: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_supported_versions_returns_every_minor_for_the_family() {
+        let registry = _versioned_protocol_registry();
+
+        let mut versions = registry.supported_versions("https://didcomm.org/connections");
+        versions.sort();
+        assert_eq!(vec![(1, 0), (1, 1)], versions);
+
+        assert!(registry
+            .supported_versions("https://didcomm.org/issue-credential")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_best_version_for_family_picks_highest_mutually_supported_minor() {
+        let registry = _versioned_protocol_registry();
+
+        let peer_versions = vec![ProtocolDescriptor {
+            pid: "https://didcomm.org/connections/1.0".to_string(),
+            roles: None,
+        }];
+
+        let best = registry
+            .get_best_version_for_family("https://didcomm.org/connections", &peer_versions)
+            .unwrap();
+        assert_eq!("https://didcomm.org/connections/1.0", best.pid);
+
+        let peer_versions = vec![
+            ProtocolDescriptor {
+                pid: "https://didcomm.org/connections/1.0".to_string(),
+                roles: None,
+            },
+            ProtocolDescriptor {
+                pid: "https://didcomm.org/connections/1.1".to_string(),
+                roles: None,
+            },
+        ];
+
+        let best = registry
+            .get_best_version_for_family("https://didcomm.org/connections", &peer_versions)
+            .unwrap();
+        assert_eq!("https://didcomm.org/connections/1.1", best.pid);
+    }
+
+    #[test]
+    fn test_get_best_version_for_family_requires_exact_major_match() {
+        let registry = _versioned_protocol_registry();
+
+        let peer_versions = vec![ProtocolDescriptor {
+            pid: "https://didcomm.org/connections/2.0".to_string(),
+            roles: None,
+        }];
+
+        assert!(registry
+            .get_best_version_for_family("https://didcomm.org/connections", &peer_versions)
+            .is_none());
+    }
 }
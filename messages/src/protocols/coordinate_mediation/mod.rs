@@ -0,0 +1,104 @@
+/// Aries RFC 0211 Coordinate Mediation message family: lets an agent request
+/// mediation from a mediator and manage the keylist the mediator routes for.
+pub const COORDINATE_MEDIATION_FAMILY: &str = "https://didcomm.org/coordinate-mediation/1.0";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediateRequest {}
+
+impl MediateRequest {
+    pub fn new() -> Self {
+        MediateRequest {}
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediateGrant {
+    pub endpoint: String,
+    pub routing_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediateDeny {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeylistUpdateAction {
+    Add,
+    Remove,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdateEntry {
+    pub recipient_key: String,
+    pub action: KeylistUpdateAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdate {
+    pub updates: Vec<KeylistUpdateEntry>,
+}
+
+impl KeylistUpdate {
+    pub fn new(updates: Vec<KeylistUpdateEntry>) -> Self {
+        KeylistUpdate { updates }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeylistUpdateResult {
+    ClientError,
+    ServerError,
+    NoChange,
+    Success,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdateResponseEntry {
+    pub recipient_key: String,
+    pub action: KeylistUpdateAction,
+    pub result: KeylistUpdateResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdateResponse {
+    pub updated: Vec<KeylistUpdateResponseEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct KeylistQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paginate: Option<KeylistQueryPaginate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistQueryPaginate {
+    pub limit: i32,
+    pub offset: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Keylist {
+    pub keys: Vec<String>,
+}
+
+#[cfg(test)]
+#[cfg(feature = "general_test")]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn keylist_update_action_serializes_lowercase() {
+        let entry = KeylistUpdateEntry {
+            recipient_key: "did:key:z123".to_string(),
+            action: KeylistUpdateAction::Add,
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["action"], "add");
+    }
+}
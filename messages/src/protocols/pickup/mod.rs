@@ -0,0 +1,132 @@
+use crate::concepts::attachment::Attachment;
+
+/// Aries RFC 0685 Pickup Protocol 2.0 message family, used by a mobile/edge agent
+/// to poll a mediator for messages it is holding on the agent's behalf.
+pub const PICKUP_PROTOCOL_FAMILY: &str = "https://didcomm.org/messagepickup/2.0";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+}
+
+impl StatusRequest {
+    pub fn new(recipient_key: Option<String>) -> Self {
+        StatusRequest { recipient_key }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Status {
+    pub message_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longest_waited_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub newest_received_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_received_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+    #[serde(default)]
+    pub live_delivery: bool,
+}
+
+impl Status {
+    /// Whether the client should immediately follow up with a `DeliveryRequest`.
+    pub fn has_pending_messages(&self) -> bool {
+        self.message_count > 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliveryRequest {
+    pub limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+}
+
+impl DeliveryRequest {
+    pub fn new(limit: u32, recipient_key: Option<String>) -> Self {
+        DeliveryRequest { limit, recipient_key }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Delivery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+    #[serde(rename = "~attach", default)]
+    pub attach: Vec<Attachment>,
+}
+
+impl Delivery {
+    /// `~attach` can legitimately be empty even when `Status.message_count` was
+    /// nonzero (e.g. the mediator purged the queue between the two messages).
+    pub fn is_empty(&self) -> bool {
+        self.attach.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessagesReceived {
+    pub message_id_list: Vec<String>,
+}
+
+impl MessagesReceived {
+    pub fn new(message_id_list: Vec<String>) -> Self {
+        MessagesReceived { message_id_list }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LiveDeliveryChange {
+    pub live_delivery: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "@type")]
+pub enum PickupMessage {
+    #[serde(rename = "https://didcomm.org/messagepickup/2.0/status-request")]
+    StatusRequest(StatusRequest),
+    #[serde(rename = "https://didcomm.org/messagepickup/2.0/status")]
+    Status(Status),
+    #[serde(rename = "https://didcomm.org/messagepickup/2.0/delivery-request")]
+    DeliveryRequest(DeliveryRequest),
+    #[serde(rename = "https://didcomm.org/messagepickup/2.0/delivery")]
+    Delivery(Delivery),
+    #[serde(rename = "https://didcomm.org/messagepickup/2.0/messages-received")]
+    MessagesReceived(MessagesReceived),
+    #[serde(rename = "https://didcomm.org/messagepickup/2.0/live-delivery-change")]
+    LiveDeliveryChange(LiveDeliveryChange),
+}
+
+#[cfg(test)]
+#[cfg(feature = "general_test")]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn status_reports_pending_messages() {
+        let status = Status {
+            message_count: 3,
+            recipient_key: None,
+            longest_waited_seconds: None,
+            newest_received_time: None,
+            oldest_received_time: None,
+            total_bytes: None,
+            live_delivery: false,
+        };
+        assert!(status.has_pending_messages());
+    }
+
+    #[test]
+    fn delivery_can_be_empty_despite_nonzero_count() {
+        let delivery = Delivery {
+            recipient_key: None,
+            attach: vec![],
+        };
+        assert!(delivery.is_empty());
+    }
+}
@@ -0,0 +1,18 @@
+use serde_json::Value;
+
+/// Aries RFC 0094 Routing `forward` message: an opaque, already-encrypted
+/// payload addressed to a recipient the sender cannot reach directly, handed
+/// to an intermediary (typically a mediator) for store-and-forward delivery.
+pub const FORWARD_MESSAGE_TYPE: &str = "https://didcomm.org/routing/1.0/forward";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Forward {
+    pub to: String,
+    pub msg: Value,
+}
+
+impl Forward {
+    pub fn new(to: String, msg: Value) -> Self {
+        Forward { to, msg }
+    }
+}